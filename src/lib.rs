@@ -1,15 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, transfer_checked, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, Burn};
+use anchor_spl::token_2022::spl_token_2022;
 
 declare_id!("2TLhCW35y5jcuoKtfwTx7H5EPMqUtCf3UQhYKdKKg3Hq");
 
 // Constants for sustainable economics
 pub const GOLD_MIN: u64 = 10_000_000 * 10u64.pow(6);      // 10M DEFAI
-pub const GOLD_MAX: u64 = 99_999_999 * 10u64.pow(6);      // 99.99M DEFAI
 pub const GOLD_APY_BPS: u16 = 50;                         // 0.5% = 50 basis points
 
 pub const TITANIUM_MIN: u64 = 100_000_000 * 10u64.pow(6); // 100M DEFAI
-pub const TITANIUM_MAX: u64 = 999_999_999 * 10u64.pow(6); // 999.99M DEFAI
 pub const TITANIUM_APY_BPS: u16 = 75;                     // 0.75% = 75 basis points
 
 pub const INFINITE_MIN: u64 = 1_000_000_000 * 10u64.pow(6); // 1B DEFAI
@@ -18,30 +17,115 @@ pub const INFINITE_APY_BPS: u16 = 100;                      // 1% = 100 basis po
 pub const SECONDS_PER_YEAR: u64 = 31_536_000;
 pub const BASIS_POINTS: u64 = 10_000;
 
+// Fixed-point scale for the per-tier reward-per-token indices
+pub const REWARD_INDEX_PRECISION: u128 = 1_000_000_000_000;
+
 // Timelock duration for admin actions
 pub const ADMIN_TIMELOCK_DURATION: i64 = 48 * 60 * 60; // 48 hours
 
-// TODO: Replace with actual program initializer authority pubkey for production
-// Example: pub const PROGRAM_INITIALIZER: Pubkey = pubkey!("ActualInitializerPubkeyHere");
-// For now, we'll add logging to track who initializes the program
+// Window after the timelock in which a proposed authority must accept
+pub const AUTHORITY_PROPOSAL_EXPIRY: i64 = 7 * 24 * 60 * 60; // 7 days
+
+// Admin roles (bit flags); the program authority is the owner and implicitly holds every role
+pub const ROLE_PAUSER: u8 = 1 << 0;          // Can pause, but not unpause
+pub const ROLE_TREASURER: u8 = 1 << 1;       // Manages escrow funding and withdrawals
+pub const ROLE_CONFIG_MANAGER: u8 = 1 << 2;  // Edits tiers, lock terms and program parameters
+pub const ALL_ROLES: u8 = ROLE_PAUSER | ROLE_TREASURER | ROLE_CONFIG_MANAGER;
+pub const MAX_ROLE_MEMBERS: usize = 16;
+
+// Built-in admin council (M-of-N multisig)
+pub const MAX_COUNCIL_SIGNERS: usize = 10;
+
+// Tier table limits
+pub const MAX_TIERS: usize = 8;
+pub const MAX_TIER_NAME_LEN: usize = 16;
+
+// Reward campaigns running at the same time
+pub const MAX_CAMPAIGNS: usize = 4;
+
+// Escrow runway guard
+pub const MAX_MIN_RUNWAY_DAYS: u16 = 365;
+
+// Fixed lock terms
+pub const MAX_LOCK_TERMS: usize = 8;
+pub const MAX_LOCK_MULTIPLIER_BPS: u16 = 30_000;          // 3x cap on boosted APY
+pub const FLEXIBLE_LOCK_DURATION: i64 = 7 * 24 * 60 * 60; // 7 days for positions without a term
+
+// Early-unstake penalty schedule for flexible positions
+pub const MAX_PENALTY_BRACKETS: usize = 8;
+pub const MAX_UNSTAKE_PENALTY_BPS: u16 = 2_000;           // 20% cap on any bracket
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+// Unbonding cooldown bounds
+pub const DEFAULT_UNBONDING_PERIOD: i64 = 14 * 24 * 60 * 60; // 14 days
+pub const MAX_UNBONDING_PERIOD: i64 = 90 * 24 * 60 * 60;     // Never longer than the penalty schedule
+
+// Pause flags, one per operation group
+pub const PAUSE_STAKE: u8 = 1 << 0;     // stake_tokens, cancel_unstake, extend_lock, join_reward_stream
+pub const PAUSE_UNSTAKE: u8 = 1 << 1;   // unstake_tokens, request_unstake, complete_unstake, leave_reward_stream
+pub const PAUSE_CLAIM: u8 = 1 << 2;     // claim_rewards, close_stake
+pub const PAUSE_COMPOUND: u8 = 1 << 3;  // compound_rewards
+pub const PAUSE_FUND: u8 = 1 << 4;      // fund_escrow
+pub const PAUSE_ALL: u8 = PAUSE_STAKE | PAUSE_UNSTAKE | PAUSE_CLAIM | PAUSE_COMPOUND | PAUSE_FUND;
+
+// Emergency exit grace period after a pause
+pub const DEFAULT_EMERGENCY_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60; // 7 days
+pub const MAX_EMERGENCY_GRACE_PERIOD: i64 = 30 * 24 * 60 * 60;    // 30 days
+pub const MIN_UNSTAKE_RESUME_WINDOW: i64 = 24 * 60 * 60;          // Unstaking must stay open this long before a re-pause restarts the grace period
+
 
 #[program]
 pub mod defai_staking {
     use super::*;
 
+    pub fn initialize_global_config(
+        ctx: Context<InitializeGlobalConfig>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        // Only the program's upgrade authority can pick who may create pools
+        let global_config = &mut ctx.accounts.global_config;
+        global_config.admin = admin;
+        global_config.bump = ctx.bumps.global_config;
+        
+        emit!(GlobalAdminUpdatedEvent {
+            old_admin: Pubkey::default(),
+            new_admin: admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn set_global_admin(
+        ctx: Context<SetGlobalAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        let global_config = &mut ctx.accounts.global_config;
+        let old_admin = global_config.admin;
+        global_config.admin = new_admin;
+        
+        emit!(GlobalAdminUpdatedEvent {
+            old_admin,
+            new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
     pub fn initialize_program(
         ctx: Context<InitializeProgram>,
         defai_mint: Pubkey,
     ) -> Result<()> {
-        // TODO: For production, uncomment and implement authority check:
-        // require_keys_eq!(
-        //     ctx.accounts.authority.key(),
-        //     PROGRAM_INITIALIZER,
-        //     StakingError::InvalidAuthority
-        // );
+        // The pool PDA is derived from the mint account, so the argument has to match it
+        require_keys_eq!(
+            defai_mint,
+            ctx.accounts.defai_mint.key(),
+            StakingError::InvalidMint
+        );
         
         // Log the initializer for audit trail
-        msg!("Program initialized by authority: {}", ctx.accounts.authority.key());
+        msg!("Pool for mint {} initialized by authority: {}", defai_mint, ctx.accounts.authority.key());
         
         let program_state = &mut ctx.accounts.program_state;
         
@@ -49,12 +133,32 @@ pub mod defai_staking {
         program_state.defai_mint = defai_mint;
         program_state.total_staked = 0;
         program_state.total_users = 0;
-        program_state.paused = false;
+        program_state.pause_flags = 0;
         program_state.vault_bump = ctx.bumps.stake_vault;
         program_state.reward_escrow_bump = 0; // Will be set in initialize_escrow
         program_state.escrow_vault_bump = 0;  // Will be set in initialize_escrow
         program_state.pending_authority = None;
         program_state.authority_change_timestamp = 0;
+        program_state.unbonding_period = DEFAULT_UNBONDING_PERIOD;
+        program_state.total_unbonding = 0;
+        program_state.auto_pause_on_deficit = false;
+        program_state.paused_at = 0;
+        program_state.emergency_grace_period = DEFAULT_EMERGENCY_GRACE_PERIOD;
+        program_state.funder_allowlist_enabled = false;
+        program_state.treasury = Pubkey::default();
+        program_state.next_stream_id = 0;
+        program_state.unstake_resumed_at = 0;
+        
+        let admin_roles = &mut ctx.accounts.admin_roles;
+        admin_roles.members = Vec::new();
+        admin_roles.bump = ctx.bumps.admin_roles;
+        
+        emit!(PoolInitializedEvent {
+            pool: program_state.key(),
+            mint: defai_mint,
+            authority: program_state.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
         Ok(())
     }
@@ -81,15 +185,490 @@ pub mod defai_staking {
         escrow.total_balance = 0;
         escrow.total_distributed = 0;
         escrow.bump = ctx.bumps.reward_escrow;
+        escrow.pending_withdrawal_amount = 0;
+        escrow.pending_withdrawal_destination = None;
+        escrow.withdrawal_unlock_timestamp = 0;
+        
+        Ok(())
+    }
+
+    pub fn initialize_tier_config(
+        ctx: Context<InitializeTierConfig>,
+    ) -> Result<()> {
+        // Verify the caller is the program authority
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            ctx.accounts.program_state.authority,
+            StakingError::InvalidAuthority
+        );
+        
+        msg!("Tier config initialized by authority: {}", ctx.accounts.authority.key());
+        
+        // Seed the table with the launch tiers
+        let tier_config = &mut ctx.accounts.tier_config;
+        tier_config.tiers = default_tiers();
+        tier_config.lock_terms = default_lock_terms();
+        tier_config.base_apy_bps = 0;
+        tier_config.pending_tiers = None;
+        tier_config.pending_lock_terms = None;
+        tier_config.pending_base_apy_bps = 0;
+        tier_config.tier_change_timestamp = 0;
+        tier_config.min_runway_days = 0;
+        tier_config.bump = ctx.bumps.tier_config;
+        
+        // Accrual starts from nothing staked at full APY
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        reward_accrual.reward_indices = [0; MAX_TIERS + 1];
+        reward_accrual.index_remainders = [0; MAX_TIERS + 1];
+        reward_accrual.last_index_update = Clock::get()?.unix_timestamp;
+        reward_accrual.tier_weights = [0; MAX_TIERS + 1];
+        reward_accrual.accrued_liabilities = 0;
+        reward_accrual.bonus_per_share = 0;
+        reward_accrual.campaigns = Vec::new();
+        reward_accrual.next_campaign_id = 0;
+        reward_accrual.reward_scale_bps = BASIS_POINTS as u16;
+        reward_accrual.bump = ctx.bumps.reward_accrual;
+        
+        Ok(())
+    }
+
+    pub fn grant_role(
+        ctx: Context<ManageRoles>,
+        member: Pubkey,
+        roles: u8,
+    ) -> Result<()> {
+        let new_roles = grant_roles(&mut ctx.accounts.admin_roles, member, roles)?;
+        
+        emit!(RolesUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            member,
+            roles: new_roles,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn revoke_role(
+        ctx: Context<ManageRoles>,
+        member: Pubkey,
+        roles: u8,
+    ) -> Result<()> {
+        let new_roles = revoke_roles(&mut ctx.accounts.admin_roles, member, roles)?;
+        
+        emit!(RolesUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            member,
+            roles: new_roles,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn initialize_admin_council(
+        ctx: Context<InitializeAdminCouncil>,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        // Verify the caller is the program authority
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            ctx.accounts.program_state.authority,
+            StakingError::InvalidAuthority
+        );
+        validate_council(&signers, threshold)?;
+        
+        msg!("Admin council initialized with {}-of-{} signers", threshold, signers.len());
+        
+        // The council only takes effect once it has accepted the program authority
+        let admin_council = &mut ctx.accounts.admin_council;
+        admin_council.signers = signers;
+        admin_council.threshold = threshold;
+        admin_council.proposal_count = 0;
+        admin_council.epoch = 0;
+        admin_council.bump = ctx.bumps.admin_council;
+        
+        emit!(AdminCouncilUpdatedEvent {
+            council: admin_council.key(),
+            signers: admin_council.signers.clone(),
+            threshold,
+            epoch: 0,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn create_admin_proposal(
+        ctx: Context<CreateAdminProposal>,
+        action: AdminAction,
+    ) -> Result<()> {
+        // Reject malformed actions up front rather than after the timelock
+        match &action {
+            AdminAction::UpdateTierConfig { tiers, lock_terms, base_apy_bps } => {
+                validate_tier_config_update(tiers, lock_terms, *base_apy_bps)?;
+            }
+            AdminAction::WithdrawEscrow { amount, .. } => {
+                require!(*amount > 0, StakingError::WithdrawalExceedsSurplus);
+            }
+            AdminAction::UpdatePenaltySchedule { brackets, split, .. } => {
+                validate_penalty_brackets(brackets)?;
+                validate_penalty_split(split)?;
+            }
+            AdminAction::UpdateCouncil { signers, threshold } => {
+                validate_council(signers, *threshold)?;
+            }
+            AdminAction::SetPauseFlags { pause_flags } => {
+                require!(pause_flags & !PAUSE_ALL == 0, StakingError::InvalidPauseFlags);
+            }
+            AdminAction::GrantRole { roles, .. } | AdminAction::RevokeRole { roles, .. } => {
+                require!(*roles != 0 && roles & !ALL_ROLES == 0, StakingError::InvalidRole);
+            }
+            AdminAction::SetTreasury { treasury } => {
+                require!(*treasury != Pubkey::default(), StakingError::InvalidTreasury);
+            }
+            AdminAction::ChangeAuthority { .. }
+            | AdminAction::AcceptAuthority
+            | AdminAction::CancelAuthorityChange => {}
+        }
+        
+        let admin_council = &mut ctx.accounts.admin_council;
+        let proposal_id = admin_council.proposal_count;
+        admin_council.proposal_count = admin_council.proposal_count.checked_add(1).unwrap();
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        let proposer = ctx.accounts.proposer.key();
+        
+        // Creating a proposal counts as the proposer's approval
+        let admin_proposal = &mut ctx.accounts.admin_proposal;
+        admin_proposal.proposal_id = proposal_id;
+        admin_proposal.proposer = proposer;
+        admin_proposal.action = action.clone();
+        admin_proposal.approvals = vec![proposer];
+        admin_proposal.council_epoch = admin_council.epoch;
+        admin_proposal.created_at = timestamp;
+        admin_proposal.threshold_reached_at = if admin_council.threshold <= 1 { timestamp } else { 0 };
+        admin_proposal.executed = false;
+        admin_proposal.bump = ctx.bumps.admin_proposal;
+        
+        emit!(AdminProposalCreatedEvent {
+            proposal_id,
+            proposer,
+            action,
+            threshold_reached_at: admin_proposal.threshold_reached_at,
+            timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn approve_admin_proposal(
+        ctx: Context<ApproveAdminProposal>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        let admin_council = &ctx.accounts.admin_council;
+        let admin_proposal = &mut ctx.accounts.admin_proposal;
+        let approver = ctx.accounts.approver.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        require!(!admin_proposal.executed, StakingError::ProposalAlreadyExecuted);
+        require!(
+            admin_proposal.council_epoch == admin_council.epoch,
+            StakingError::StaleAdminProposal
+        );
+        require!(
+            !admin_proposal.approvals.contains(&approver),
+            StakingError::ProposalAlreadyApproved
+        );
+        
+        admin_proposal.approvals.push(approver);
+        
+        // The timelock starts once, when the threshold is first reached
+        if admin_proposal.threshold_reached_at == 0
+            && admin_proposal.approvals.len() >= admin_council.threshold as usize
+        {
+            admin_proposal.threshold_reached_at = timestamp;
+        }
+        
+        emit!(AdminProposalApprovedEvent {
+            proposal_id,
+            approver,
+            approvals: admin_proposal.approvals.len() as u8,
+            threshold: admin_council.threshold,
+            threshold_reached_at: admin_proposal.threshold_reached_at,
+            timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn execute_admin_proposal<'info>(
+        ctx: Context<'_, '_, '_, 'info, ExecuteAdminProposal<'info>>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let council_key = ctx.accounts.admin_council.key();
+        
+        {
+            let admin_proposal = &ctx.accounts.admin_proposal;
+            require!(!admin_proposal.executed, StakingError::ProposalAlreadyExecuted);
+            require!(
+                admin_proposal.council_epoch == ctx.accounts.admin_council.epoch,
+                StakingError::StaleAdminProposal
+            );
+            require!(
+                admin_proposal.threshold_reached_at > 0,
+                StakingError::ProposalThresholdNotReached
+            );
+            require!(
+                timestamp >= admin_proposal.threshold_reached_at + ADMIN_TIMELOCK_DURATION,
+                StakingError::TimelockNotExpired
+            );
+        }
+        
+        let action = ctx.accounts.admin_proposal.action.clone();
+        
+        // Everything except taking over the authority requires the council to already hold it
+        if action != AdminAction::AcceptAuthority {
+            require_keys_eq!(
+                ctx.accounts.program_state.authority,
+                council_key,
+                StakingError::InvalidAuthority
+            );
+        }
+        
+        match action {
+            AdminAction::SetPauseFlags { pause_flags } => {
+                let program_state = &mut ctx.accounts.program_state;
+                let previous_flags = program_state.pause_flags;
+                set_pause_flags(program_state, pause_flags, timestamp);
+                
+                emit!(ProgramPausedEvent {
+                    authority: council_key,
+                    pause_flags,
+                    previous_flags,
+                    timestamp,
+                });
+            }
+            AdminAction::ChangeAuthority { new_authority } => {
+                let program_state = &mut ctx.accounts.program_state;
+                require!(
+                    program_state.pending_authority.is_none()
+                        || authority_proposal_expired(program_state, timestamp),
+                    StakingError::AuthorityChangeAlreadyPending
+                );
+                
+                // The council timelock has already run, so the incoming authority can accept right away
+                program_state.pending_authority = Some(new_authority);
+                program_state.authority_change_timestamp = timestamp;
+                
+                emit!(AuthorityProposedEvent {
+                    current_authority: council_key,
+                    proposed_authority: new_authority,
+                    executable_at: timestamp,
+                    expires_at: timestamp + AUTHORITY_PROPOSAL_EXPIRY,
+                    timestamp,
+                });
+            }
+            AdminAction::AcceptAuthority => {
+                let program_state = &mut ctx.accounts.program_state;
+                require!(
+                    program_state.pending_authority == Some(council_key),
+                    StakingError::NoPendingAuthorityChange
+                );
+                require!(
+                    timestamp >= program_state.authority_change_timestamp,
+                    StakingError::TimelockNotExpired
+                );
+                require!(
+                    !authority_proposal_expired(program_state, timestamp),
+                    StakingError::AuthorityProposalExpired
+                );
+                
+                let old_authority = program_state.authority;
+                program_state.authority = council_key;
+                program_state.pending_authority = None;
+                program_state.authority_change_timestamp = 0;
+                
+                emit!(AuthorityUpdatedEvent {
+                    old_authority,
+                    new_authority: council_key,
+                    timestamp,
+                });
+            }
+            AdminAction::UpdateTierConfig { tiers, lock_terms, base_apy_bps } => {
+                let tier_config = &mut ctx.accounts.tier_config;
+                let reward_accrual = &mut ctx.accounts.reward_accrual;
+                
+                // Close out accrual at the old rates so the new table only applies going forward
+                update_reward_indices(reward_accrual, tier_config, timestamp)?;
+                validate_staked_tiers_retained(reward_accrual, &tiers)?;
+                
+                tier_config.tiers = tiers;
+                tier_config.lock_terms = lock_terms;
+                tier_config.base_apy_bps = base_apy_bps;
+                
+                emit!(TierConfigUpdatedEvent {
+                    authority: council_key,
+                    tiers: tier_config.tiers.clone(),
+                    lock_terms: tier_config.lock_terms.clone(),
+                    base_apy_bps,
+                    timestamp,
+                });
+                
+                let reward_escrow = ctx.accounts.reward_escrow
+                    .as_ref()
+                    .ok_or(StakingError::MissingProposalAccount)?;
+                apply_runway_guard(reward_accrual, tier_config, reward_escrow.total_balance, timestamp)?;
+            }
+            AdminAction::WithdrawEscrow { amount, destination } => {
+                let accounts = &mut *ctx.accounts;
+                let (
+                    Some(reward_escrow),
+                    Some(escrow_token_account),
+                    Some(destination_token_account),
+                    Some(defai_mint),
+                    Some(token_program),
+                ) = (
+                    accounts.reward_escrow.as_mut(),
+                    accounts.escrow_token_account.as_ref(),
+                    accounts.destination_token_account.as_ref(),
+                    accounts.defai_mint.as_ref(),
+                    accounts.token_program.as_ref(),
+                ) else {
+                    return err!(StakingError::MissingProposalAccount);
+                };
+                withdraw_escrow_surplus(
+                    EscrowWithdrawal {
+                        program_state: &accounts.program_state,
+                        tier_config: &accounts.tier_config,
+                        reward_accrual: &mut accounts.reward_accrual,
+                        reward_escrow,
+                        escrow_token_account,
+                        destination_token_account,
+                        defai_mint,
+                        token_program,
+                    },
+                    ctx.remaining_accounts,
+                    amount,
+                    destination,
+                    council_key,
+                    timestamp,
+                )?;
+            }
+            AdminAction::UpdatePenaltySchedule { brackets, linear_decay, split } => {
+                require!(
+                    split.treasury_bps == 0 || ctx.accounts.program_state.treasury != Pubkey::default(),
+                    StakingError::InvalidTreasury
+                );
+                
+                let penalty_config = ctx.accounts.penalty_config
+                    .as_mut()
+                    .ok_or(StakingError::MissingProposalAccount)?;
+                penalty_config.brackets = brackets;
+                penalty_config.linear_decay = linear_decay;
+                penalty_config.split = split;
+                
+                emit!(PenaltyScheduleUpdatedEvent {
+                    authority: council_key,
+                    brackets: penalty_config.brackets.clone(),
+                    linear_decay,
+                    split,
+                    timestamp,
+                });
+            }
+            AdminAction::UpdateCouncil { signers, threshold } => {
+                // Bumping the epoch invalidates every other in-flight proposal
+                let admin_council = &mut ctx.accounts.admin_council;
+                admin_council.signers = signers;
+                admin_council.threshold = threshold;
+                admin_council.epoch = admin_council.epoch.checked_add(1).unwrap();
+                
+                emit!(AdminCouncilUpdatedEvent {
+                    council: council_key,
+                    signers: admin_council.signers.clone(),
+                    threshold,
+                    epoch: admin_council.epoch,
+                    timestamp,
+                });
+            }
+            AdminAction::GrantRole { member, roles } | AdminAction::RevokeRole { member, roles } => {
+                let admin_roles = ctx.accounts.admin_roles
+                    .as_mut()
+                    .ok_or(StakingError::MissingProposalAccount)?;
+                let new_roles = if matches!(action, AdminAction::GrantRole { .. }) {
+                    grant_roles(admin_roles, member, roles)?
+                } else {
+                    revoke_roles(admin_roles, member, roles)?
+                };
+                
+                emit!(RolesUpdatedEvent {
+                    authority: council_key,
+                    member,
+                    roles: new_roles,
+                    timestamp,
+                });
+            }
+            AdminAction::SetTreasury { treasury } => {
+                let treasury_token_account = ctx.accounts.treasury_token_account
+                    .as_ref()
+                    .ok_or(StakingError::MissingProposalAccount)?;
+                require_keys_eq!(treasury_token_account.key(), treasury, StakingError::InvalidTreasury);
+                
+                let program_state = &mut ctx.accounts.program_state;
+                program_state.treasury = treasury;
+                
+                emit!(TreasuryUpdatedEvent {
+                    authority: council_key,
+                    treasury,
+                    timestamp,
+                });
+            }
+            AdminAction::CancelAuthorityChange => {
+                let cancelled_authority = clear_pending_authority(&mut ctx.accounts.program_state)?;
+                
+                emit!(AuthorityChangeCancelledEvent {
+                    authority: council_key,
+                    cancelled_authority,
+                    timestamp,
+                });
+            }
+        }
+        
+        ctx.accounts.admin_proposal.executed = true;
+        
+        emit!(AdminProposalExecutedEvent {
+            proposal_id,
+            executor: ctx.accounts.executor.key(),
+            timestamp,
+        });
         
         Ok(())
     }
 
-    pub fn fund_escrow(
-        ctx: Context<FundEscrow>,
+    pub fn fund_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundEscrow<'info>>,
         amount: u64,
     ) -> Result<()> {
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_FUND), StakingError::ProgramPaused);
+        
+        let funder_record = &mut ctx.accounts.funder_record;
+        if funder_record.funder == Pubkey::default() {
+            // First contribution from an unregistered funder
+            funder_record.funder = ctx.accounts.funder.key();
+            funder_record.approved = false;
+            funder_record.bump = ctx.bumps.funder_record;
+        }
+        
+        // With the allowlist on, only funders added by the authority may top up the escrow
+        require!(
+            !ctx.accounts.program_state.funder_allowlist_enabled || funder_record.approved,
+            StakingError::FunderNotApproved
+        );
+        
         // Transfer tokens from funder to escrow
+        let vault_balance_before = ctx.accounts.escrow_token_account.amount;
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             TransferChecked {
@@ -98,35 +677,120 @@ pub mod defai_staking {
                 authority: ctx.accounts.funder.to_account_info(),
                 mint: ctx.accounts.defai_mint.to_account_info(),
             },
-        );
-        transfer_checked(transfer_ctx, amount, ctx.accounts.defai_mint.decimals)?;
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.defai_mint.decimals)?;
+        let amount = received_amount(&mut ctx.accounts.escrow_token_account, vault_balance_before)?;
+        let timestamp = Clock::get()?.unix_timestamp;
         
         // Update escrow balance
         let escrow = &mut ctx.accounts.reward_escrow;
         escrow.total_balance = escrow.total_balance.checked_add(amount).unwrap();
         
+        // Attribute the contribution to the funder
+        let funder_record = &mut ctx.accounts.funder_record;
+        funder_record.total_contributed = funder_record.total_contributed.checked_add(amount).unwrap();
+        funder_record.last_funded_at = timestamp;
+        
         emit!(EscrowFundedEvent {
             funder: ctx.accounts.funder.key(),
             amount,
             new_balance: escrow.total_balance,
+            funder_total_contributed: funder_record.total_contributed,
+        });
+        
+        // New funds can lift a runway throttle right away
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, timestamp)?;
+        apply_runway_guard(reward_accrual, tier_config, escrow.total_balance, timestamp)?;
+        
+        Ok(())
+    }
+
+    pub fn add_funder(
+        ctx: Context<AddFunder>,
+        funder: Pubkey,
+    ) -> Result<()> {
+        let funder_record = &mut ctx.accounts.funder_record;
+        if funder_record.funder == Pubkey::default() {
+            funder_record.funder = funder;
+            funder_record.bump = ctx.bumps.funder_record;
+        }
+        funder_record.approved = true;
+        
+        emit!(FunderUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            funder,
+            approved: true,
+            total_contributed: funder_record.total_contributed,
+        });
+        
+        Ok(())
+    }
+
+    pub fn remove_funder(
+        ctx: Context<RemoveFunder>,
+        funder: Pubkey,
+    ) -> Result<()> {
+        // Keep the record so past contributions stay attributable
+        let funder_record = &mut ctx.accounts.funder_record;
+        funder_record.approved = false;
+        
+        emit!(FunderUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            funder,
+            approved: false,
+            total_contributed: funder_record.total_contributed,
+        });
+        
+        Ok(())
+    }
+
+    pub fn set_funder_allowlist(
+        ctx: Context<UpdateTreasuryConfig>,
+        enabled: bool,
+    ) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.funder_allowlist_enabled = enabled;
+        
+        msg!("Funder allowlist set to {}", enabled);
+        
+        Ok(())
+    }
+
+    pub fn set_treasury(ctx: Context<SetTreasury>) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.treasury = ctx.accounts.treasury_token_account.key();
+        
+        emit!(TreasuryUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            treasury: program_state.treasury,
+            timestamp: Clock::get()?.unix_timestamp,
         });
         
         Ok(())
     }
 
-    pub fn stake_tokens(
-        ctx: Context<StakeTokens>,
+    pub fn stake_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeTokens<'info>>,
         amount: u64,
+        lock_days: u16,
     ) -> Result<()> {
         let program_state = &ctx.accounts.program_state;
         
         // Check if program is paused
-        require!(!program_state.paused, StakingError::ProgramPaused);
+        require!(!program_state.is_paused(PAUSE_STAKE), StakingError::ProgramPaused);
         
-        // Check minimum amount for Gold tier
-        require!(amount >= GOLD_MIN, StakingError::AmountTooLow);
+        // 0 selects the flexible position, anything else must match a configured term
+        let (lock_duration, apy_multiplier_bps) = if lock_days == 0 {
+            (FLEXIBLE_LOCK_DURATION, BASIS_POINTS as u16)
+        } else {
+            let term = find_lock_term(&ctx.accounts.tier_config, lock_days)?;
+            (term.duration_days as i64 * SECONDS_PER_DAY, term.multiplier_bps)
+        };
         
         // Transfer tokens from user to stake vault
+        let vault_balance_before = ctx.accounts.stake_vault.amount;
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
             TransferChecked {
@@ -135,45 +799,61 @@ pub mod defai_staking {
                 authority: ctx.accounts.user.to_account_info(),
                 mint: ctx.accounts.defai_mint.to_account_info(),
             },
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.defai_mint.decimals)?;
+        
+        // Credit what actually arrived, which is less than `amount` for transfer-fee mints
+        let amount = received_amount(&mut ctx.accounts.stake_vault, vault_balance_before)?;
+        
+        // Check minimum amount for the lowest tier
+        require!(
+            amount >= min_stake_amount(&ctx.accounts.tier_config)?,
+            StakingError::AmountTooLow
         );
-        transfer_checked(transfer_ctx, amount, ctx.accounts.defai_mint.decimals)?;
         
-        // Create or update user stake account
-        let user_stake = &mut ctx.accounts.user_stake;
         let clock = Clock::get()?;
         
-        if user_stake.owner == Pubkey::default() {
-            // New stake
-            user_stake.owner = ctx.accounts.user.key();
-            user_stake.staked_amount = amount;
-            user_stake.stake_timestamp = clock.unix_timestamp;
-            user_stake.last_stake_timestamp = clock.unix_timestamp;  // Set both timestamps for new stake
-            user_stake.last_claim_timestamp = clock.unix_timestamp;
-            user_stake.locked_until = clock.unix_timestamp + 7 * 24 * 60 * 60; // 7 day initial lock
-            user_stake.rewards_earned = 0;
-            user_stake.rewards_claimed = 0;
-            
+        // Every stake opens a fresh position with its own lock and penalty clock
+        let position_counter = &mut ctx.accounts.position_counter;
+        if position_counter.owner == Pubkey::default() {
+            position_counter.owner = ctx.accounts.user.key();
+            position_counter.bump = ctx.bumps.position_counter;
+        }
+        let position_id = position_counter.next_position_id;
+        position_counter.next_position_id = position_counter.next_position_id.checked_add(1).unwrap();
+        position_counter.open_positions = position_counter.open_positions.checked_add(1).unwrap();
+        
+        if position_counter.open_positions == 1 {
             // Update global stats
             let program_state = &mut ctx.accounts.program_state;
             program_state.total_users += 1;
-        } else {
-            // Calculate pending rewards before adding new stake
-            let pending_rewards = calculate_rewards(
-                user_stake.staked_amount,
-                get_tier_apy(user_stake.staked_amount)?,
-                user_stake.last_claim_timestamp,
-                clock.unix_timestamp,
-            )?;
-            
-            user_stake.rewards_earned = user_stake.rewards_earned.checked_add(pending_rewards).unwrap();
-            user_stake.staked_amount = user_stake.staked_amount.checked_add(amount).unwrap();
-            user_stake.last_claim_timestamp = clock.unix_timestamp;
-            user_stake.last_stake_timestamp = clock.unix_timestamp;  // Update last stake timestamp on additional stakes
-            user_stake.locked_until = clock.unix_timestamp + 7 * 24 * 60 * 60; // Extend lock period for additional stakes
         }
         
-        // Update tier based on new total
-        user_stake.tier = get_tier(user_stake.staked_amount)?;
+        // Bring the reward indices up to date before touching any balances
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.owner = ctx.accounts.user.key();
+        user_stake.position_id = position_id;
+        user_stake.staked_amount = amount;
+        user_stake.stake_timestamp = clock.unix_timestamp;
+        user_stake.last_stake_timestamp = clock.unix_timestamp;  // Set both timestamps for new stake
+        user_stake.last_claim_timestamp = clock.unix_timestamp;
+        user_stake.locked_until = clock.unix_timestamp + lock_duration;
+        user_stake.lock_days = lock_days;
+        user_stake.apy_multiplier_bps = apy_multiplier_bps;
+        user_stake.rewards_earned = 0;
+        user_stake.rewards_claimed = 0;
+        user_stake.stake_seconds_updated_at = clock.unix_timestamp;
+        user_stake.stream_positions = 0;
+        user_stake.open_tickets = 0;
+        set_tier(user_stake, tier_config, reward_accrual)?;
+        
+        // Aggregate tier across all of the wallet's positions
+        position_counter.total_staked = position_counter.total_staked.checked_add(amount).unwrap();
+        set_aggregate_tier(position_counter, tier_config);
         
         // Update total staked
         let program_state = &mut ctx.accounts.program_state;
@@ -181,20 +861,27 @@ pub mod defai_staking {
         
         emit!(StakeEvent {
             user: ctx.accounts.user.key(),
+            position_id,
             amount,
             tier: user_stake.tier,
-            total_staked: user_stake.staked_amount,
+            lock_days,
+            locked_until: user_stake.locked_until,
+            total_staked: position_counter.total_staked,
+            aggregate_tier: position_counter.aggregate_tier,
         });
         
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
         Ok(())
     }
 
-    pub fn unstake_tokens(
-        ctx: Context<UnstakeTokens>,
+    pub fn unstake_tokens<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnstakeTokens<'info>>,
+        position_id: u64,
         amount: u64,
     ) -> Result<()> {
         // Enforce pause
-        require!(!ctx.accounts.program_state.paused, StakingError::ProgramPaused);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_UNSTAKE), StakingError::ProgramPaused);
         let user_stake = &mut ctx.accounts.user_stake;
         let clock = Clock::get()?;
         
@@ -210,22 +897,24 @@ pub mod defai_staking {
             StakingError::InsufficientStake
         );
         
-        // Calculate pending rewards before unstaking
-        let pending_rewards = calculate_rewards(
-            user_stake.staked_amount,
-            get_tier_apy(user_stake.staked_amount)?,
-            user_stake.last_claim_timestamp,
-            clock.unix_timestamp,
-        )?;
-        user_stake.rewards_earned = user_stake.rewards_earned.checked_add(pending_rewards).unwrap();
+        // Settle pending rewards before unstaking
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
         user_stake.last_claim_timestamp = clock.unix_timestamp;
         
-        // Calculate unstaking penalty using last stake timestamp
-        let penalty = calculate_unstake_penalty(
-            user_stake.last_stake_timestamp,
-            clock.unix_timestamp,
-            amount,
-        )?;
+        // Term positions unlock penalty-free at term end; flexible ones use the penalty schedule
+        let penalty_bps = if user_stake.lock_days > 0 {
+            0
+        } else {
+            unstake_penalty_bps(
+                &ctx.accounts.penalty_config,
+                user_stake.last_stake_timestamp,
+                clock.unix_timestamp,
+            )
+        };
+        let penalty = calculate_unstake_penalty(amount, penalty_bps)?;
         
         let amount_after_penalty = amount.checked_sub(penalty).unwrap();
         
@@ -247,11 +936,20 @@ pub mod defai_staking {
                 mint: ctx.accounts.defai_mint.to_account_info(),
             },
             signer,
-        );
-        transfer_checked(transfer_ctx, amount_after_penalty, ctx.accounts.defai_mint.decimals)?;
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount_after_penalty, ctx.accounts.defai_mint.decimals)?;
         
-        // If there's a penalty, transfer it to escrow
-        if penalty > 0 {
+        // Route the penalty per the configured split; rounding dust stays with the escrow
+        let split = ctx.accounts.penalty_config.split;
+        let penalty_to_treasury = (penalty as u128 * split.treasury_bps as u128 / BASIS_POINTS as u128) as u64;
+        let penalty_burned = (penalty as u128 * split.burn_bps as u128 / BASIS_POINTS as u128) as u64;
+        let penalty_to_escrow = penalty
+            .checked_sub(penalty_to_treasury).unwrap()
+            .checked_sub(penalty_burned).unwrap();
+        
+        let mut penalty_to_escrow = penalty_to_escrow;
+        if penalty_to_escrow > 0 {
+            let vault_balance_before = ctx.accounts.escrow_token_account.amount;
             let transfer_penalty_ctx = CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 TransferChecked {
@@ -261,91 +959,342 @@ pub mod defai_staking {
                     mint: ctx.accounts.defai_mint.to_account_info(),
                 },
                 signer,
-            );
-            transfer_checked(transfer_penalty_ctx, penalty, ctx.accounts.defai_mint.decimals)?;
+            ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_penalty_ctx, penalty_to_escrow, ctx.accounts.defai_mint.decimals)?;
+            penalty_to_escrow = received_amount(&mut ctx.accounts.escrow_token_account, vault_balance_before)?;
             
             // Update escrow balance
             let escrow = &mut ctx.accounts.reward_escrow;
-            escrow.total_balance = escrow.total_balance.checked_add(penalty).unwrap();
+            escrow.total_balance = escrow.total_balance.checked_add(penalty_to_escrow).unwrap();
+        }
+        
+        if penalty_to_treasury > 0 {
+            let treasury_token_account = ctx.accounts.treasury_token_account
+                .as_ref()
+                .ok_or(StakingError::InvalidTreasury)?;
+            
+            let transfer_treasury_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    to: treasury_token_account.to_account_info(),
+                    authority: ctx.accounts.stake_vault.to_account_info(),
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                },
+                signer,
+            ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_treasury_ctx, penalty_to_treasury, ctx.accounts.defai_mint.decimals)?;
+        }
+        
+        if penalty_burned > 0 {
+            let burn_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                    from: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.stake_vault.to_account_info(),
+                },
+                signer,
+            );
+            burn_checked(burn_ctx, penalty_burned, ctx.accounts.defai_mint.decimals)?;
         }
         
         // Update user stake
         user_stake.staked_amount = user_stake.staked_amount.checked_sub(amount).unwrap();
         
         // Update tier
-        if user_stake.staked_amount > 0 {
-            user_stake.tier = get_tier(user_stake.staked_amount)?;
-        } else {
-            user_stake.tier = 0;
-        }
+        set_tier(user_stake, &ctx.accounts.tier_config, &mut ctx.accounts.reward_accrual)?;
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        position_counter.total_staked = position_counter.total_staked.checked_sub(amount).unwrap();
+        set_aggregate_tier(position_counter, &ctx.accounts.tier_config);
         
         // Update global stats
         let program_state = &mut ctx.accounts.program_state;
         program_state.total_staked = program_state.total_staked.checked_sub(amount).unwrap();
         
+        // Optionally hand the escrow share to the other stakers; this position's remainder
+        // must not earn back part of its own penalty
+        let other_staked = program_state.total_staked.checked_sub(user_stake.staked_amount).unwrap();
+        let penalty_distributed = ctx.accounts.penalty_config.distribute_to_stakers
+            && penalty_to_escrow > 0
+            && other_staked > 0;
+        if penalty_distributed {
+            let reward_accrual = &mut ctx.accounts.reward_accrual;
+            distribute_bonus(reward_accrual, penalty_to_escrow, other_staked)?;
+            user_stake.bonus_checkpoint = reward_accrual.bonus_per_share;
+            
+            emit!(BonusDistributedEvent {
+                amount: penalty_to_escrow,
+                total_staked: other_staked,
+                bonus_per_share: reward_accrual.bonus_per_share,
+                timestamp: clock.unix_timestamp,
+            });
+        }
+        
         emit!(UnstakeEvent {
             user: ctx.accounts.user.key(),
+            position_id,
             amount,
             penalty,
+            penalty_bps,
+            penalty_to_escrow,
+            penalty_to_treasury,
+            penalty_burned,
+            penalty_distributed,
             remaining_stake: user_stake.staked_amount,
             new_tier: user_stake.tier,
+            aggregate_tier: position_counter.aggregate_tier,
         });
         
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
         Ok(())
     }
 
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    pub fn request_unstake(
+        ctx: Context<RequestUnstake>,
+        position_id: u64,
+        amount: u64,
+    ) -> Result<()> {
         // Enforce pause
-        require!(!ctx.accounts.program_state.paused, StakingError::ProgramPaused);
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_UNSTAKE), StakingError::ProgramPaused);
         let user_stake = &mut ctx.accounts.user_stake;
         let clock = Clock::get()?;
         
-        // Calculate pending rewards
-        let pending_rewards = calculate_rewards(
-            user_stake.staked_amount,
-            get_tier_apy(user_stake.staked_amount)?,
-            user_stake.last_claim_timestamp,
-            clock.unix_timestamp,
-        )?;
-        
-        let total_claimable = user_stake.rewards_earned
-            .checked_add(pending_rewards).unwrap()
-            .checked_sub(user_stake.rewards_claimed).unwrap();
-        
-        require!(total_claimable > 0, StakingError::NoRewards);
+        // Check if tokens are locked
+        require!(
+            clock.unix_timestamp >= user_stake.locked_until,
+            StakingError::TokensLocked
+        );
         
-        // Check escrow has sufficient balance
+        // Check sufficient balance
         require!(
-            ctx.accounts.reward_escrow.total_balance >= total_claimable,
-            StakingError::InsufficientEscrowBalance
+            amount > 0 && user_stake.staked_amount >= amount,
+            StakingError::InsufficientStake
         );
         
-        // Transfer rewards from escrow to user
-        let program_state_key = ctx.accounts.program_state.key();
-        let escrow_seeds = &[
-            b"reward-escrow",
-            program_state_key.as_ref(),
-            &[ctx.accounts.program_state.reward_escrow_bump],
-        ];
-        let escrow_signer = &[&escrow_seeds[..]];
+        // Settle pending rewards; the unbonding amount stops earning from here on
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        user_stake.last_claim_timestamp = clock.unix_timestamp;
         
-        let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.escrow_token_account.to_account_info(),
-                to: ctx.accounts.user_token_account.to_account_info(),
-                authority: ctx.accounts.reward_escrow.to_account_info(),
+        user_stake.staked_amount = user_stake.staked_amount.checked_sub(amount).unwrap();
+        user_stake.open_tickets = user_stake.open_tickets.checked_add(1).ok_or(StakingError::TooManyOpenTickets)?;
+        set_tier(user_stake, tier_config, reward_accrual)?;
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        let ticket_id = position_counter.next_ticket_id;
+        position_counter.next_ticket_id = position_counter.next_ticket_id.checked_add(1).unwrap();
+        position_counter.total_staked = position_counter.total_staked.checked_sub(amount).unwrap();
+        set_aggregate_tier(position_counter, tier_config);
+        
+        // Tokens stay in the stake vault but no longer count towards total_staked
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.total_staked = program_state.total_staked.checked_sub(amount).unwrap();
+        program_state.total_unbonding = program_state.total_unbonding.checked_add(amount).unwrap();
+        
+        let ticket = &mut ctx.accounts.unbonding_ticket;
+        ticket.owner = ctx.accounts.user.key();
+        ticket.ticket_id = ticket_id;
+        ticket.position_id = position_id;
+        ticket.amount = amount;
+        ticket.requested_at = clock.unix_timestamp;
+        ticket.claimable_at = clock.unix_timestamp + program_state.unbonding_period;
+        ticket.bump = ctx.bumps.unbonding_ticket;
+        
+        emit!(UnstakeRequestedEvent {
+            user: ctx.accounts.user.key(),
+            position_id,
+            ticket_id,
+            amount,
+            claimable_at: ticket.claimable_at,
+            aggregate_tier: position_counter.aggregate_tier,
+        });
+        
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+
+    pub fn complete_unstake<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompleteUnstake<'info>>,
+        ticket_id: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        
+        // Enforce pause, unless the emergency exit has opened
+        require!(
+            !ctx.accounts.program_state.is_paused(PAUSE_UNSTAKE)
+                || emergency_exit_open(&ctx.accounts.program_state, clock.unix_timestamp),
+            StakingError::ProgramPaused
+        );
+        let ticket = &ctx.accounts.unbonding_ticket;
+        
+        require!(
+            clock.unix_timestamp >= ticket.claimable_at,
+            StakingError::UnbondingNotComplete
+        );
+        
+        // Release the full amount, no penalty after the cooldown
+        let program_state_key = ctx.accounts.program_state.key();
+        let seeds = &[
+            b"stake-vault",
+            program_state_key.as_ref(),
+            &[ctx.accounts.program_state.vault_bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.stake_vault.to_account_info(),
                 mint: ctx.accounts.defai_mint.to_account_info(),
             },
-            escrow_signer,
+            signer,
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, ticket.amount, ctx.accounts.defai_mint.decimals)?;
+        
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.total_unbonding = program_state.total_unbonding.checked_sub(ticket.amount).unwrap();
+        
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.open_tickets = user_stake.open_tickets.checked_sub(1).unwrap();
+        
+        emit!(UnstakeCompletedEvent {
+            user: ctx.accounts.user.key(),
+            position_id: ticket.position_id,
+            ticket_id,
+            amount: ticket.amount,
+        });
+        
+        Ok(())
+    }
+
+    pub fn cancel_unstake(
+        ctx: Context<CancelUnstake>,
+        ticket_id: u64,
+    ) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_STAKE), StakingError::ProgramPaused);
+        let user_stake = &mut ctx.accounts.user_stake;
+        let amount = ctx.accounts.unbonding_ticket.amount;
+        let clock = Clock::get()?;
+        
+        // Settle the position before the ticket amount starts earning again
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        user_stake.last_claim_timestamp = clock.unix_timestamp;
+        
+        user_stake.staked_amount = user_stake.staked_amount.checked_add(amount).unwrap();
+        user_stake.open_tickets = user_stake.open_tickets.checked_sub(1).unwrap();
+        set_tier(user_stake, tier_config, reward_accrual)?;
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        position_counter.total_staked = position_counter.total_staked.checked_add(amount).unwrap();
+        set_aggregate_tier(position_counter, tier_config);
+        
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.total_staked = program_state.total_staked.checked_add(amount).unwrap();
+        program_state.total_unbonding = program_state.total_unbonding.checked_sub(amount).unwrap();
+        
+        emit!(UnstakeCancelledEvent {
+            user: ctx.accounts.user.key(),
+            position_id: user_stake.position_id,
+            ticket_id,
+            amount,
+            new_tier: user_stake.tier,
+        });
+        
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+
+    pub fn update_unbonding_period(
+        ctx: Context<UpdateProgramConfig>,
+        unbonding_period: i64,
+    ) -> Result<()> {
+        require!(
+            unbonding_period > 0 && unbonding_period <= MAX_UNBONDING_PERIOD,
+            StakingError::InvalidUnbondingPeriod
+        );
+        
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.unbonding_period = unbonding_period;
+        
+        emit!(UnbondingPeriodUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            unbonding_period,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>, position_id: u64) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIM), StakingError::ProgramPaused);
+        let user_stake = &mut ctx.accounts.user_stake;
+        let clock = Clock::get()?;
+        
+        // Settle pending rewards
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        
+        // Tier rewards plus any redistributed penalty bonus
+        let bonus_claimable = user_stake.bonus_earned
+            .checked_sub(user_stake.bonus_claimed).unwrap();
+        let total_claimable = user_stake.rewards_earned
+            .checked_sub(user_stake.rewards_claimed).unwrap()
+            .checked_add(bonus_claimable).unwrap();
+        
+        require!(total_claimable > 0, StakingError::NoRewards);
+        
+        // Check escrow has sufficient balance
+        require!(
+            ctx.accounts.reward_escrow.total_balance >= total_claimable,
+            StakingError::InsufficientEscrowBalance
         );
-        transfer_checked(transfer_ctx, total_claimable, ctx.accounts.defai_mint.decimals)?;
+        
+        // Transfer rewards from escrow to user
+        let program_state_key = ctx.accounts.program_state.key();
+        let escrow_seeds = &[
+            b"reward-escrow",
+            program_state_key.as_ref(),
+            &[ctx.accounts.program_state.reward_escrow_bump],
+        ];
+        let escrow_signer = &[&escrow_seeds[..]];
+        
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.reward_escrow.to_account_info(),
+                mint: ctx.accounts.defai_mint.to_account_info(),
+            },
+            escrow_signer,
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, total_claimable, ctx.accounts.defai_mint.decimals)?;
         
         // Update user stake
-        user_stake.rewards_earned = user_stake.rewards_earned.checked_add(pending_rewards).unwrap();
-        user_stake.rewards_claimed = user_stake.rewards_claimed.checked_add(total_claimable).unwrap();
+        user_stake.rewards_claimed = user_stake.rewards_earned;
+        user_stake.bonus_claimed = user_stake.bonus_earned;
         user_stake.last_claim_timestamp = clock.unix_timestamp;
         
+        // Paid out rewards are no longer a liability
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.saturating_sub(total_claimable);
+        
         // Update escrow
         let escrow = &mut ctx.accounts.reward_escrow;
         escrow.total_balance = escrow.total_balance.checked_sub(total_claimable).unwrap();
@@ -353,10 +1302,189 @@ pub mod defai_staking {
         
         emit!(RewardsClaimedEvent {
             user: ctx.accounts.user.key(),
+            position_id,
             amount: total_claimable,
+            bonus_amount: bonus_claimable,
             total_distributed: escrow.total_distributed,
         });
         
+        // Re-check the escrow runway against everything accrued up to now
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+
+    pub fn close_stake<'info>(ctx: Context<'_, '_, '_, 'info, CloseStake<'info>>, position_id: u64) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIM), StakingError::ProgramPaused);
+        let user_stake = &mut ctx.accounts.user_stake;
+        let clock = Clock::get()?;
+        
+        // Only fully exited positions can be closed
+        require!(user_stake.staked_amount == 0, StakingError::StakeNotEmpty);
+        require!(user_stake.stream_positions == 0, StakingError::StreamPositionsOpen);
+        require!(user_stake.open_tickets == 0, StakingError::UnbondingTicketsOpen);
+        
+        // Settle anything accrued up to the final unstake
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        
+        let total_claimable = user_stake.rewards_earned
+            .checked_sub(user_stake.rewards_claimed).unwrap()
+            .checked_add(user_stake.bonus_earned.checked_sub(user_stake.bonus_claimed).unwrap()).unwrap();
+        
+        if total_claimable > 0 {
+            // Check escrow has sufficient balance
+            require!(
+                ctx.accounts.reward_escrow.total_balance >= total_claimable,
+                StakingError::InsufficientEscrowBalance
+            );
+            
+            // Pay out remaining rewards before the account is closed
+            let program_state_key = ctx.accounts.program_state.key();
+            let escrow_seeds = &[
+                b"reward-escrow",
+                program_state_key.as_ref(),
+                &[ctx.accounts.program_state.reward_escrow_bump],
+            ];
+            let escrow_signer = &[&escrow_seeds[..]];
+            
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_escrow.to_account_info(),
+                    mint: ctx.accounts.defai_mint.to_account_info(),
+                },
+                escrow_signer,
+            ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, total_claimable, ctx.accounts.defai_mint.decimals)?;
+            
+            user_stake.rewards_claimed = user_stake.rewards_earned;
+            user_stake.bonus_claimed = user_stake.bonus_earned;
+            
+            let reward_accrual = &mut ctx.accounts.reward_accrual;
+            reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.saturating_sub(total_claimable);
+            
+            let escrow = &mut ctx.accounts.reward_escrow;
+            escrow.total_balance = escrow.total_balance.checked_sub(total_claimable).unwrap();
+            escrow.total_distributed = escrow.total_distributed.checked_add(total_claimable).unwrap();
+        }
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        position_counter.open_positions = position_counter.open_positions.checked_sub(1).unwrap();
+        
+        // The wallet stops counting as a user once its last position is closed
+        if position_counter.open_positions == 0 {
+            let program_state = &mut ctx.accounts.program_state;
+            program_state.total_users = program_state.total_users.checked_sub(1).unwrap();
+        }
+        
+        emit!(StakeClosedEvent {
+            user: ctx.accounts.user.key(),
+            position_id,
+            rewards_paid: total_claimable,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+
+    pub fn propose_escrow_withdrawal(
+        ctx: Context<ProposeEscrowWithdrawal>,
+        amount: u64,
+        destination: Pubkey,
+    ) -> Result<()> {
+        require!(amount > 0, StakingError::WithdrawalExceedsSurplus);
+        
+        // A pending withdrawal has to be cancelled first
+        let escrow = &mut ctx.accounts.reward_escrow;
+        require!(
+            escrow.pending_withdrawal_destination.is_none(),
+            StakingError::EscrowWithdrawalAlreadyPending
+        );
+        escrow.pending_withdrawal_amount = amount;
+        escrow.pending_withdrawal_destination = Some(destination);
+        escrow.withdrawal_unlock_timestamp = Clock::get()?.unix_timestamp + ADMIN_TIMELOCK_DURATION;
+        
+        emit!(EscrowWithdrawalProposedEvent {
+            authority: ctx.accounts.authority.key(),
+            amount,
+            destination,
+            unlock_timestamp: escrow.withdrawal_unlock_timestamp,
+        });
+        
+        msg!("Escrow withdrawal proposed. Can be executed after {}", escrow.withdrawal_unlock_timestamp);
+        
+        Ok(())
+    }
+
+    pub fn execute_escrow_withdrawal<'info>(ctx: Context<'_, '_, '_, 'info, ExecuteEscrowWithdrawal<'info>>) -> Result<()> {
+        let escrow = &ctx.accounts.reward_escrow;
+        let clock = Clock::get()?;
+        
+        let destination = escrow.pending_withdrawal_destination
+            .ok_or(StakingError::NoPendingEscrowWithdrawal)?;
+        require!(
+            clock.unix_timestamp >= escrow.withdrawal_unlock_timestamp,
+            StakingError::TimelockNotExpired
+        );
+        let amount = escrow.pending_withdrawal_amount;
+        
+        let authority = ctx.accounts.authority.key();
+        let accounts = &mut *ctx.accounts;
+        withdraw_escrow_surplus(
+            EscrowWithdrawal {
+                program_state: &accounts.program_state,
+                tier_config: &accounts.tier_config,
+                reward_accrual: &mut accounts.reward_accrual,
+                reward_escrow: &mut accounts.reward_escrow,
+                escrow_token_account: &accounts.escrow_token_account,
+                destination_token_account: &accounts.destination_token_account,
+                defai_mint: &accounts.defai_mint,
+                token_program: &accounts.token_program,
+            },
+            ctx.remaining_accounts,
+            amount,
+            destination,
+            authority,
+            clock.unix_timestamp,
+        )?;
+        
+        let escrow = &mut ctx.accounts.reward_escrow;
+        escrow.pending_withdrawal_amount = 0;
+        escrow.pending_withdrawal_destination = None;
+        escrow.withdrawal_unlock_timestamp = 0;
+        
+        Ok(())
+    }
+    
+    pub fn cancel_escrow_withdrawal(
+        ctx: Context<ProposeEscrowWithdrawal>,
+    ) -> Result<()> {
+        let escrow = &mut ctx.accounts.reward_escrow;
+        
+        let destination = escrow.pending_withdrawal_destination
+            .ok_or(StakingError::NoPendingEscrowWithdrawal)?;
+        let amount = escrow.pending_withdrawal_amount;
+        escrow.pending_withdrawal_amount = 0;
+        escrow.pending_withdrawal_destination = None;
+        escrow.withdrawal_unlock_timestamp = 0;
+        
+        emit!(EscrowWithdrawalCancelledEvent {
+            authority: ctx.accounts.authority.key(),
+            amount,
+            destination,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Escrow withdrawal of {} cancelled", amount);
+        
         Ok(())
     }
 
@@ -365,13 +1493,24 @@ pub mod defai_staking {
         new_authority: Pubkey,
     ) -> Result<()> {
         let program_state = &mut ctx.accounts.program_state;
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        // A live proposal has to be cancelled first; lapsed ones can be replaced
+        require!(
+            program_state.pending_authority.is_none()
+                || authority_proposal_expired(program_state, timestamp),
+            StakingError::AuthorityChangeAlreadyPending
+        );
+        
         program_state.pending_authority = Some(new_authority);
-        program_state.authority_change_timestamp = Clock::get()?.unix_timestamp + ADMIN_TIMELOCK_DURATION;
+        program_state.authority_change_timestamp = timestamp + ADMIN_TIMELOCK_DURATION;
         
-        emit!(AuthorityUpdatedEvent {
-            old_authority: program_state.authority,
-            new_authority,
-            timestamp: Clock::get()?.unix_timestamp,
+        emit!(AuthorityProposedEvent {
+            current_authority: program_state.authority,
+            proposed_authority: new_authority,
+            executable_at: program_state.authority_change_timestamp,
+            expires_at: program_state.authority_change_timestamp + AUTHORITY_PROPOSAL_EXPIRY,
+            timestamp,
         });
         
         msg!("Authority change proposed. Can be executed after {}", program_state.authority_change_timestamp);
@@ -379,19 +1518,41 @@ pub mod defai_staking {
         Ok(())
     }
     
-    pub fn accept_authority_change(
+    pub fn cancel_authority_change(
         ctx: Context<UpdateAuthority>,
     ) -> Result<()> {
         let program_state = &mut ctx.accounts.program_state;
+        let cancelled_authority = clear_pending_authority(program_state)?;
+        
+        emit!(AuthorityChangeCancelledEvent {
+            authority: program_state.authority,
+            cancelled_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Authority change to {} cancelled", cancelled_authority);
+        
+        Ok(())
+    }
+    
+    pub fn accept_authority_change(
+        ctx: Context<AcceptAuthority>,
+    ) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        let timestamp = Clock::get()?.unix_timestamp;
         
         require!(
             program_state.pending_authority.is_some(),
             StakingError::NoPendingAuthorityChange
         );
         require!(
-            Clock::get()?.unix_timestamp >= program_state.authority_change_timestamp,
+            timestamp >= program_state.authority_change_timestamp,
             StakingError::TimelockNotExpired
         );
+        require!(
+            !authority_proposal_expired(program_state, timestamp),
+            StakingError::AuthorityProposalExpired
+        );
         
         let old_authority = program_state.authority;
         let new_authority = program_state.pending_authority.unwrap();
@@ -402,7 +1563,7 @@ pub mod defai_staking {
         emit!(AuthorityUpdatedEvent {
             old_authority,
             new_authority,
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp,
         });
         
         msg!("Authority changed from {} to {}", old_authority, new_authority);
@@ -410,301 +1571,3234 @@ pub mod defai_staking {
         Ok(())
     }
 
-    pub fn pause_program(ctx: Context<PauseProgram>, paused: bool) -> Result<()> {
-        let program_state = &mut ctx.accounts.program_state;
-        program_state.paused = paused;
+    pub fn propose_tier_config_update(
+        ctx: Context<UpdateTierConfig>,
+        tiers: Vec<TierInfo>,
+        lock_terms: Vec<LockTerm>,
+        base_apy_bps: u16,
+    ) -> Result<()> {
+        validate_tier_config_update(&tiers, &lock_terms, base_apy_bps)?;
         
-        emit!(ProgramPausedEvent {
-            authority: ctx.accounts.authority.key(),
-            paused,
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        // A pending change has to be cancelled first
+        let tier_config = &mut ctx.accounts.tier_config;
+        require!(
+            tier_config.pending_tiers.is_none(),
+            StakingError::TierConfigChangeAlreadyPending
+        );
+        tier_config.pending_tiers = Some(tiers);
+        tier_config.pending_lock_terms = Some(lock_terms);
+        tier_config.pending_base_apy_bps = base_apy_bps;
+        tier_config.tier_change_timestamp = Clock::get()?.unix_timestamp + ADMIN_TIMELOCK_DURATION;
+        
+        msg!("Tier config change proposed. Can be executed after {}", tier_config.tier_change_timestamp);
         
         Ok(())
     }
     
-    pub fn compound_rewards(ctx: Context<CompoundRewards>) -> Result<()> {
-        // Enforce pause
-        require!(!ctx.accounts.program_state.paused, StakingError::ProgramPaused);
-        let user_stake = &mut ctx.accounts.user_stake;
-        let clock = Clock::get()?;
+    pub fn cancel_tier_config_update(
+        ctx: Context<UpdateTierConfig>,
+    ) -> Result<()> {
+        let tier_config = &mut ctx.accounts.tier_config;
         
-        // Calculate pending rewards
-        let pending_rewards = calculate_rewards(
-            user_stake.staked_amount,
-            get_tier_apy(user_stake.staked_amount)?,
-            user_stake.last_claim_timestamp,
-            clock.unix_timestamp,
-        )?;
+        require!(
+            tier_config.pending_tiers.is_some(),
+            StakingError::NoPendingTierConfigChange
+        );
+        tier_config.pending_tiers = None;
+        tier_config.pending_lock_terms = None;
+        tier_config.pending_base_apy_bps = 0;
+        tier_config.tier_change_timestamp = 0;
         
-        let total_unclaimed = user_stake.rewards_earned
-            .checked_add(pending_rewards).unwrap()
-            .checked_sub(user_stake.rewards_claimed).unwrap();
+        emit!(TierConfigChangeCancelledEvent {
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
-        require!(total_unclaimed > 0, StakingError::NoRewards);
+        msg!("Tier config change cancelled");
+        
+        Ok(())
+    }
+    
+    pub fn accept_tier_config_update(
+        ctx: Context<UpdateTierConfig>,
+    ) -> Result<()> {
+        let tier_config = &mut ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
         
-        // Check escrow has sufficient balance
         require!(
-            ctx.accounts.reward_escrow.total_balance >= total_unclaimed,
-            StakingError::InsufficientEscrowBalance
+            tier_config.pending_tiers.is_some(),
+            StakingError::NoPendingTierConfigChange
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= tier_config.tier_change_timestamp,
+            StakingError::TimelockNotExpired
         );
         
-        // Update stake amount by adding rewards
-        let old_staked = user_stake.staked_amount;
-        user_stake.staked_amount = user_stake.staked_amount
-            .checked_add(total_unclaimed).unwrap();
+        // Close out accrual at the old rates so the new table only applies going forward
+        update_reward_indices(reward_accrual, tier_config, Clock::get()?.unix_timestamp)?;
+        validate_staked_tiers_retained(reward_accrual, tier_config.pending_tiers.as_ref().unwrap())?;
         
-        // Update tier based on new amount
-        let old_tier = user_stake.tier;
-        user_stake.tier = get_tier(user_stake.staked_amount)?;
+        tier_config.tiers = tier_config.pending_tiers.take().unwrap();
+        tier_config.lock_terms = tier_config.pending_lock_terms.take().unwrap_or_default();
+        tier_config.base_apy_bps = tier_config.pending_base_apy_bps;
+        tier_config.pending_base_apy_bps = 0;
+        tier_config.tier_change_timestamp = 0;
         
-        // Update reward tracking
-        user_stake.rewards_earned = user_stake.rewards_earned.checked_add(pending_rewards).unwrap();
-        user_stake.rewards_claimed = user_stake.rewards_earned; // Mark all as claimed since compounded
-        user_stake.last_claim_timestamp = clock.unix_timestamp;
+        emit!(TierConfigUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            tiers: tier_config.tiers.clone(),
+            lock_terms: tier_config.lock_terms.clone(),
+            base_apy_bps: tier_config.base_apy_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         
-        // Reduce escrow balance (rewards stay in vault as part of stake)
-        let escrow = &mut ctx.accounts.reward_escrow;
-        escrow.total_balance = escrow.total_balance.checked_sub(total_unclaimed).unwrap();
-        escrow.total_distributed = escrow.total_distributed.checked_add(total_unclaimed).unwrap();
+        msg!("Tier config updated with {} tiers", tier_config.tiers.len());
         
-        // Update global staked amount
+        // The new rates change the projected burn rate
+        apply_runway_guard(reward_accrual, tier_config, ctx.accounts.reward_escrow.total_balance, Clock::get()?.unix_timestamp)?;
+        
+        Ok(())
+    }
+    
+    pub fn create_reward_campaign(
+        ctx: Context<ManageRewardCampaign>,
+        start_timestamp: i64,
+        end_timestamp: i64,
+        eligible_tiers: u16,
+        emission_per_second: u64,
+        total_budget: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(
+            start_timestamp >= clock.unix_timestamp
+                && end_timestamp > start_timestamp
+                && eligible_tiers != 0
+                && eligible_tiers >> (MAX_TIERS + 1) == 0
+                && emission_per_second > 0
+                && total_budget > 0,
+            StakingError::InvalidCampaign
+        );
+        
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        require!(reward_accrual.campaigns.len() < MAX_CAMPAIGNS, StakingError::TooManyCampaigns);
+        
+        // The whole budget must be covered by escrow funds nothing else has claimed
+        let surplus = ctx.accounts.reward_escrow.total_balance.saturating_sub(reward_accrual.committed_rewards());
+        require!(total_budget <= surplus, StakingError::InsufficientEscrowBalance);
+        
+        let campaign = RewardCampaign {
+            campaign_id: reward_accrual.next_campaign_id,
+            start_timestamp,
+            end_timestamp,
+            eligible_tiers,
+            emission_per_second,
+            total_budget,
+            distributed: 0,
+        };
+        reward_accrual.next_campaign_id = reward_accrual.next_campaign_id.checked_add(1).unwrap();
+        
+        emit!(RewardCampaignCreatedEvent {
+            authority: ctx.accounts.authority.key(),
+            campaign: campaign.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+        reward_accrual.campaigns.push(campaign);
+        
+        // The reserved budget is no longer available to tier APY
+        apply_runway_guard(reward_accrual, tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+    
+    pub fn cancel_reward_campaign(
+        ctx: Context<ManageRewardCampaign>,
+        campaign_id: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        
+        // Emissions up to now stay with the stakers; the unspent budget is released
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        let position = reward_accrual.campaigns
+            .iter()
+            .position(|campaign| campaign.campaign_id == campaign_id)
+            .ok_or(StakingError::CampaignNotFound)?;
+        let campaign = reward_accrual.campaigns.remove(position);
+        
+        emit!(RewardCampaignEndedEvent {
+            campaign_id,
+            distributed: campaign.distributed,
+            cancelled: true,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        apply_runway_guard(reward_accrual, tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+    
+    pub fn set_min_runway_days(
+        ctx: Context<UpdateTierConfig>,
+        min_runway_days: u16,
+    ) -> Result<()> {
+        require!(min_runway_days <= MAX_MIN_RUNWAY_DAYS, StakingError::InvalidRunwayConfig);
+        
+        let timestamp = Clock::get()?.unix_timestamp;
+        let tier_config = &mut ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, timestamp)?;
+        tier_config.min_runway_days = min_runway_days;
+        apply_runway_guard(reward_accrual, tier_config, ctx.accounts.reward_escrow.total_balance, timestamp)?;
+        
+        msg!("Minimum escrow runway set to {} days", min_runway_days);
+        
+        Ok(())
+    }
+    
+    // Permissionless: settles accrual and re-evaluates the runway scale, e.g. after escrow funding
+    pub fn refresh_runway(ctx: Context<RefreshRunway>) -> Result<()> {
+        let clock = Clock::get()?;
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        apply_runway_guard(reward_accrual, tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+
+    pub fn initialize_penalty_config(
+        ctx: Context<InitializePenaltyConfig>,
+    ) -> Result<()> {
+        // Verify the caller is the program authority
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            ctx.accounts.program_state.authority,
+            StakingError::InvalidAuthority
+        );
+        
+        msg!("Penalty config initialized by authority: {}", ctx.accounts.authority.key());
+        
+        // Seed with the launch schedule: 2% under 30 days, 1% under 90 days, free after
+        let penalty_config = &mut ctx.accounts.penalty_config;
+        penalty_config.brackets = default_penalty_brackets();
+        penalty_config.linear_decay = false;
+        penalty_config.pending_brackets = None;
+        penalty_config.pending_linear_decay = false;
+        penalty_config.penalty_change_timestamp = 0;
+        penalty_config.bump = ctx.bumps.penalty_config;
+        
+        // Everything goes back to the reward escrow until a split is proposed
+        penalty_config.split = PenaltySplit {
+            escrow_bps: BASIS_POINTS as u16,
+            treasury_bps: 0,
+            burn_bps: 0,
+        };
+        penalty_config.pending_split = PenaltySplit::default();
+        penalty_config.distribute_to_stakers = false;
+        
+        emit!(PenaltyScheduleUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            brackets: penalty_config.brackets.clone(),
+            linear_decay: false,
+            split: penalty_config.split,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn propose_penalty_schedule_update(
+        ctx: Context<UpdatePenaltyConfig>,
+        brackets: Vec<PenaltyBracket>,
+        linear_decay: bool,
+        split: PenaltySplit,
+    ) -> Result<()> {
+        validate_penalty_brackets(&brackets)?;
+        validate_penalty_split(&split)?;
+        
+        // A pending change has to be cancelled first
+        let timestamp = Clock::get()?.unix_timestamp;
+        let penalty_config = &mut ctx.accounts.penalty_config;
+        require!(
+            penalty_config.pending_brackets.is_none(),
+            StakingError::PenaltyChangeAlreadyPending
+        );
+        penalty_config.pending_brackets = Some(brackets.clone());
+        penalty_config.pending_linear_decay = linear_decay;
+        penalty_config.pending_split = split;
+        penalty_config.penalty_change_timestamp = timestamp + ADMIN_TIMELOCK_DURATION;
+        
+        emit!(PenaltyScheduleProposedEvent {
+            authority: ctx.accounts.authority.key(),
+            brackets,
+            linear_decay,
+            split,
+            executable_at: penalty_config.penalty_change_timestamp,
+            timestamp,
+        });
+        
+        msg!("Penalty schedule change proposed. Can be executed after {}", penalty_config.penalty_change_timestamp);
+        
+        Ok(())
+    }
+    
+    pub fn cancel_penalty_schedule_update(
+        ctx: Context<UpdatePenaltyConfig>,
+    ) -> Result<()> {
+        let penalty_config = &mut ctx.accounts.penalty_config;
+        
+        require!(
+            penalty_config.pending_brackets.is_some(),
+            StakingError::NoPendingPenaltyChange
+        );
+        penalty_config.pending_brackets = None;
+        penalty_config.pending_linear_decay = false;
+        penalty_config.pending_split = PenaltySplit::default();
+        penalty_config.penalty_change_timestamp = 0;
+        
+        emit!(PenaltyScheduleChangeCancelledEvent {
+            authority: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Penalty schedule change cancelled");
+        
+        Ok(())
+    }
+    
+    pub fn accept_penalty_schedule_update(
+        ctx: Context<UpdatePenaltyConfig>,
+    ) -> Result<()> {
+        let penalty_config = &mut ctx.accounts.penalty_config;
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        require!(
+            penalty_config.pending_brackets.is_some(),
+            StakingError::NoPendingPenaltyChange
+        );
+        require!(
+            timestamp >= penalty_config.penalty_change_timestamp,
+            StakingError::TimelockNotExpired
+        );
+        
+        // A treasury share needs somewhere to go
+        require!(
+            penalty_config.pending_split.treasury_bps == 0
+                || ctx.accounts.program_state.treasury != Pubkey::default(),
+            StakingError::InvalidTreasury
+        );
+        
+        penalty_config.brackets = penalty_config.pending_brackets.take().unwrap();
+        penalty_config.linear_decay = penalty_config.pending_linear_decay;
+        penalty_config.split = penalty_config.pending_split;
+        penalty_config.pending_linear_decay = false;
+        penalty_config.pending_split = PenaltySplit::default();
+        penalty_config.penalty_change_timestamp = 0;
+        
+        emit!(PenaltyScheduleUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            brackets: penalty_config.brackets.clone(),
+            linear_decay: penalty_config.linear_decay,
+            split: penalty_config.split,
+            timestamp,
+        });
+        
+        msg!("Penalty schedule updated with {} brackets", penalty_config.brackets.len());
+        
+        Ok(())
+    }
+
+    pub fn set_penalty_distribution(
+        ctx: Context<UpdatePenaltyConfig>,
+        distribute_to_stakers: bool,
+    ) -> Result<()> {
+        let penalty_config = &mut ctx.accounts.penalty_config;
+        penalty_config.distribute_to_stakers = distribute_to_stakers;
+        
+        emit!(PenaltyDistributionUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            distribute_to_stakers,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        msg!("Penalty distribution to stakers set to {}", distribute_to_stakers);
+        
+        Ok(())
+    }
+
+    pub fn check_invariants(ctx: Context<CheckInvariants>) -> Result<()> {
+        let stake_vault_balance = ctx.accounts.stake_vault.amount;
+        let escrow_vault_balance = ctx.accounts.escrow_token_account.amount;
+        let escrow_total_balance = ctx.accounts.reward_escrow.total_balance;
+        let clock = Clock::get()?;
+        
+        // Stake vault backs both active stakes and unbonding tickets
         let program_state = &mut ctx.accounts.program_state;
-        program_state.total_staked = program_state.total_staked.checked_add(total_unclaimed).unwrap();
+        let stake_liabilities = program_state.total_staked
+            .checked_add(program_state.total_unbonding).unwrap();
         
-        msg!(
-            "Compounded {} rewards. Stake: {} -> {}, Tier: {} -> {}",
-            total_unclaimed,
-            old_staked,
-            user_stake.staked_amount,
-            old_tier,
-            user_stake.tier
+        let stake_vault_delta = stake_vault_balance as i128 - stake_liabilities as i128;
+        let escrow_delta = escrow_vault_balance as i128 - escrow_total_balance as i128;
+        let deficit = stake_vault_delta < 0 || escrow_delta < 0;
+        
+        if deficit && program_state.auto_pause_on_deficit && program_state.pause_flags != PAUSE_ALL {
+            let previous_flags = program_state.pause_flags;
+            set_pause_flags(program_state, PAUSE_ALL, clock.unix_timestamp);
+            
+            emit!(ProgramPausedEvent {
+                authority: ctx.accounts.caller.key(),
+                pause_flags: PAUSE_ALL,
+                previous_flags,
+                timestamp: clock.unix_timestamp,
+            });
+            
+            msg!("Vault deficit detected, program paused");
+        }
+        
+        emit!(ReconciliationEvent {
+            stake_vault_balance,
+            total_staked: program_state.total_staked,
+            total_unbonding: program_state.total_unbonding,
+            stake_vault_delta,
+            escrow_vault_balance,
+            escrow_total_balance,
+            escrow_delta,
+            deficit,
+            pause_flags: program_state.pause_flags,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn sync_escrow_balance(ctx: Context<SyncEscrowBalance>) -> Result<()> {
+        let escrow_vault_balance = ctx.accounts.escrow_token_account.amount;
+        let escrow = &mut ctx.accounts.reward_escrow;
+        
+        // Only credit tokens that arrived outside of fund_escrow (direct donations)
+        require!(
+            escrow_vault_balance > escrow.total_balance,
+            StakingError::NoUnaccountedEscrowBalance
         );
+        let unaccounted = escrow_vault_balance - escrow.total_balance;
+        escrow.total_balance = escrow_vault_balance;
         
-        emit!(RewardsCompoundedEvent {
+        emit!(EscrowSyncedEvent {
+            authority: ctx.accounts.authority.key(),
+            amount: unaccounted,
+            new_balance: escrow.total_balance,
+        });
+        
+        Ok(())
+    }
+
+    pub fn set_auto_pause_on_deficit(
+        ctx: Context<UpdateProgramConfig>,
+        enabled: bool,
+    ) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.auto_pause_on_deficit = enabled;
+        
+        msg!("Auto-pause on deficit set to {}", enabled);
+        
+        Ok(())
+    }
+
+    pub fn extend_lock(
+        ctx: Context<ExtendLock>,
+        position_id: u64,
+        lock_days: u16,
+    ) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_STAKE), StakingError::ProgramPaused);
+        let user_stake = &mut ctx.accounts.user_stake;
+        let clock = Clock::get()?;
+        
+        // Positions can only roll into a strictly longer term
+        require!(lock_days > user_stake.lock_days, StakingError::InvalidLockTerm);
+        let term = find_lock_term(&ctx.accounts.tier_config, lock_days)?;
+        let multiplier_bps = term.multiplier_bps;
+        let locked_until = clock.unix_timestamp + term.duration_days as i64 * SECONDS_PER_DAY;
+        
+        // Settle at the old multiplier before boosting
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        user_stake.last_claim_timestamp = clock.unix_timestamp;
+        
+        user_stake.lock_days = lock_days;
+        user_stake.apy_multiplier_bps = multiplier_bps;
+        user_stake.locked_until = user_stake.locked_until.max(locked_until);
+        set_tier(user_stake, tier_config, reward_accrual)?;
+        
+        emit!(LockExtendedEvent {
             user: ctx.accounts.user.key(),
-            amount_compounded: total_unclaimed,
-            new_stake_amount: user_stake.staked_amount,
+            position_id,
+            lock_days,
+            apy_multiplier_bps: multiplier_bps,
+            locked_until: user_stake.locked_until,
+        });
+        
+        // The boosted multiplier raises the projected burn rate
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+    
+    // Permissionless: moves a position onto the tier its amount maps to under the current table,
+    // e.g. after a tier-config change, without waiting for the owner to touch it
+    pub fn retier_position(
+        ctx: Context<RetierPosition>,
+        position_id: u64,
+    ) -> Result<()> {
+        let user_stake = &mut ctx.accounts.user_stake;
+        let clock = Clock::get()?;
+        let old_tier = user_stake.tier;
+        
+        // Settle at the old tier, then move the weight
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        set_tier(user_stake, tier_config, reward_accrual)?;
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        set_aggregate_tier(position_counter, tier_config);
+        
+        emit!(PositionRetieredEvent {
+            owner: user_stake.owner,
+            position_id,
             old_tier,
             new_tier: user_stake.tier,
+            aggregate_tier: position_counter.aggregate_tier,
             timestamp: clock.unix_timestamp,
         });
         
+        // The new tier can accrue at a different rate
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
         Ok(())
     }
-}
 
-// Account structures
-#[account]
-pub struct ProgramState {
-    pub authority: Pubkey,
-    pub defai_mint: Pubkey,
-    pub total_staked: u64,
-    pub total_users: u64,
-    pub paused: bool,
-    pub vault_bump: u8,              // Bump for stake-vault PDA
-    pub reward_escrow_bump: u8,      // Bump for reward-escrow PDA
-    pub escrow_vault_bump: u8,       // Bump for escrow-vault PDA (token account)
-    pub pending_authority: Option<Pubkey>,
-    pub authority_change_timestamp: i64,
-}
+    pub fn pause_program(ctx: Context<PauseProgram>, pause_flags: u8) -> Result<()> {
+        require!(pause_flags & !PAUSE_ALL == 0, StakingError::InvalidPauseFlags);
+        
+        let program_state = &mut ctx.accounts.program_state;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let previous_flags = program_state.pause_flags;
+        
+        // Pausers hold a hot key for incidents; clearing any flag is reserved for the owner
+        if pause_flags & previous_flags != previous_flags {
+            require_keys_eq!(
+                ctx.accounts.authority.key(),
+                program_state.authority,
+                StakingError::InvalidAuthority
+            );
+        }
+        
+        set_pause_flags(program_state, pause_flags, timestamp);
+        
+        emit!(ProgramPausedEvent {
+            authority: ctx.accounts.authority.key(),
+            pause_flags,
+            previous_flags,
+            timestamp,
+        });
+        
+        Ok(())
+    }
 
-#[account]
-pub struct RewardEscrow {
-    pub authority: Pubkey,
-    pub total_balance: u64,
-    pub total_distributed: u64,
-    pub bump: u8,
+    pub fn update_emergency_grace_period(
+        ctx: Context<UpdateProgramConfig>,
+        emergency_grace_period: i64,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_EMERGENCY_GRACE_PERIOD).contains(&emergency_grace_period),
+            StakingError::InvalidEmergencyGracePeriod
+        );
+        
+        // Cannot move the goalposts during a pause, or right before re-pausing
+        let program_state = &mut ctx.accounts.program_state;
+        require!(
+            !program_state.is_paused(PAUSE_UNSTAKE)
+                && unstake_resume_window_elapsed(program_state, Clock::get()?.unix_timestamp),
+            StakingError::GracePeriodLocked
+        );
+        
+        program_state.emergency_grace_period = emergency_grace_period;
+        
+        msg!("Emergency grace period set to {} seconds", emergency_grace_period);
+        
+        Ok(())
+    }
+
+    pub fn emergency_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, EmergencyWithdraw<'info>>,
+        position_id: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        
+        // Only available once the program has been paused for the full grace period
+        require!(
+            emergency_exit_open(&ctx.accounts.program_state, clock.unix_timestamp),
+            StakingError::EmergencyExitNotAvailable
+        );
+        
+        let user_stake = &mut ctx.accounts.user_stake;
+        let amount = user_stake.staked_amount;
+        require!(amount > 0, StakingError::InsufficientStake);
+        
+        // Principal only: settled and pending rewards are forfeited, no penalty and no lock
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        let rewards_forfeited = user_stake.rewards_earned
+            .checked_sub(user_stake.rewards_claimed).unwrap()
+            .checked_add(user_stake.bonus_earned.checked_sub(user_stake.bonus_claimed).unwrap()).unwrap();
+        reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.saturating_sub(rewards_forfeited);
+        
+        let program_state_key = ctx.accounts.program_state.key();
+        let seeds = &[
+            b"stake-vault",
+            program_state_key.as_ref(),
+            &[ctx.accounts.program_state.vault_bump],
+        ];
+        let signer = &[&seeds[..]];
+        
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.stake_vault.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.stake_vault.to_account_info(),
+                mint: ctx.accounts.defai_mint.to_account_info(),
+            },
+            signer,
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.defai_mint.decimals)?;
+        
+        user_stake.staked_amount = 0;
+        user_stake.rewards_earned = user_stake.rewards_claimed;
+        user_stake.bonus_earned = user_stake.bonus_claimed;
+        user_stake.last_claim_timestamp = clock.unix_timestamp;
+        set_tier(user_stake, &ctx.accounts.tier_config, &mut ctx.accounts.reward_accrual)?;
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        position_counter.total_staked = position_counter.total_staked.checked_sub(amount).unwrap();
+        set_aggregate_tier(position_counter, &ctx.accounts.tier_config);
+        
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.total_staked = program_state.total_staked.checked_sub(amount).unwrap();
+        
+        emit!(EmergencyWithdrawEvent {
+            user: ctx.accounts.user.key(),
+            position_id,
+            amount,
+            rewards_forfeited,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+    
+    pub fn compound_rewards<'info>(ctx: Context<'_, '_, '_, 'info, CompoundRewards<'info>>, position_id: u64) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_COMPOUND), StakingError::ProgramPaused);
+        let user_stake = &mut ctx.accounts.user_stake;
+        let clock = Clock::get()?;
+        
+        // Settle pending rewards
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        update_reward_indices(reward_accrual, tier_config, clock.unix_timestamp)?;
+        accrue_rewards(user_stake, reward_accrual)?;
+        
+        let total_unclaimed = user_stake.rewards_earned
+            .checked_sub(user_stake.rewards_claimed).unwrap()
+            .checked_add(user_stake.bonus_earned.checked_sub(user_stake.bonus_claimed).unwrap()).unwrap();
+        
+        require!(total_unclaimed > 0, StakingError::NoRewards);
+        
+        // Check escrow has sufficient balance
+        require!(
+            ctx.accounts.reward_escrow.total_balance >= total_unclaimed,
+            StakingError::InsufficientEscrowBalance
+        );
+        
+        // Move the compounded rewards from the escrow vault into the stake vault
+        let vault_balance_before = ctx.accounts.stake_vault.amount;
+        let program_state_key = ctx.accounts.program_state.key();
+        let escrow_seeds = &[
+            b"reward-escrow",
+            program_state_key.as_ref(),
+            &[ctx.accounts.program_state.reward_escrow_bump],
+        ];
+        let escrow_signer = &[&escrow_seeds[..]];
+        
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.escrow_token_account.to_account_info(),
+                to: ctx.accounts.stake_vault.to_account_info(),
+                authority: ctx.accounts.reward_escrow.to_account_info(),
+                mint: ctx.accounts.defai_mint.to_account_info(),
+            },
+            escrow_signer,
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, total_unclaimed, ctx.accounts.defai_mint.decimals)?;
+        
+        // The escrow pays the full amount; only what reached the stake vault is staked
+        let amount_staked = received_amount(&mut ctx.accounts.stake_vault, vault_balance_before)?;
+        
+        // Update stake amount by adding rewards
+        let user_stake = &mut ctx.accounts.user_stake;
+        let tier_config = &ctx.accounts.tier_config;
+        let reward_accrual = &mut ctx.accounts.reward_accrual;
+        let old_staked = user_stake.staked_amount;
+        user_stake.staked_amount = user_stake.staked_amount
+            .checked_add(amount_staked).unwrap();
+        
+        // Update tier based on new amount
+        let old_tier = user_stake.tier;
+        set_tier(user_stake, tier_config, reward_accrual)?;
+        
+        // Update reward tracking
+        user_stake.rewards_claimed = user_stake.rewards_earned; // Mark all as claimed since compounded
+        user_stake.bonus_claimed = user_stake.bonus_earned;
+        user_stake.last_claim_timestamp = clock.unix_timestamp;
+        
+        // Reduce escrow balance; compounded rewards are no longer a liability
+        reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.saturating_sub(total_unclaimed);
+        let escrow = &mut ctx.accounts.reward_escrow;
+        escrow.total_balance = escrow.total_balance.checked_sub(total_unclaimed).unwrap();
+        escrow.total_distributed = escrow.total_distributed.checked_add(total_unclaimed).unwrap();
+        
+        let position_counter = &mut ctx.accounts.position_counter;
+        position_counter.total_staked = position_counter.total_staked.checked_add(amount_staked).unwrap();
+        set_aggregate_tier(position_counter, tier_config);
+        
+        // Update global staked amount
+        let program_state = &mut ctx.accounts.program_state;
+        program_state.total_staked = program_state.total_staked.checked_add(amount_staked).unwrap();
+        
+        msg!(
+            "Compounded {} rewards. Stake: {} -> {}, Tier: {} -> {}",
+            total_unclaimed,
+            old_staked,
+            user_stake.staked_amount,
+            old_tier,
+            user_stake.tier
+        );
+        
+        emit!(RewardsCompoundedEvent {
+            user: ctx.accounts.user.key(),
+            position_id,
+            amount_compounded: total_unclaimed,
+            new_stake_amount: user_stake.staked_amount,
+            old_tier,
+            new_tier: user_stake.tier,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        apply_runway_guard(&mut ctx.accounts.reward_accrual, &ctx.accounts.tier_config, ctx.accounts.reward_escrow.total_balance, clock.unix_timestamp)?;
+        
+        Ok(())
+    }
+    
+    pub fn create_reward_stream(
+        ctx: Context<CreateRewardStream>,
+        tier_rates: Vec<u64>,
+    ) -> Result<()> {
+        // Rates are keyed by tier id, slot 0 being stakes below the lowest tier
+        require!(
+            !tier_rates.is_empty() && tier_rates.len() <= MAX_TIERS + 1,
+            StakingError::InvalidRewardStream
+        );
+        
+        let clock = Clock::get()?;
+        let program_state = &mut ctx.accounts.program_state;
+        let stream_id = program_state.next_stream_id;
+        program_state.next_stream_id = program_state.next_stream_id.checked_add(1).unwrap();
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.stream_id = stream_id;
+        reward_stream.reward_mint = ctx.accounts.reward_mint.key();
+        reward_stream.authority = ctx.accounts.authority.key();
+        reward_stream.tier_rates = [0; MAX_TIERS + 1];
+        reward_stream.tier_rates[..tier_rates.len()].copy_from_slice(&tier_rates);
+        reward_stream.total_balance = 0;
+        reward_stream.total_distributed = 0;
+        reward_stream.created_at = clock.unix_timestamp;
+        reward_stream.bump = ctx.bumps.reward_stream;
+        reward_stream.vault_bump = ctx.bumps.stream_vault;
+        reward_stream.closes_at = 0;
+        reward_stream.participants = 0;
+        
+        emit!(RewardStreamCreatedEvent {
+            stream_id,
+            reward_mint: reward_stream.reward_mint,
+            tier_rates,
+            authority: reward_stream.authority,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn fund_reward_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, FundRewardStream<'info>>,
+        stream_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_FUND), StakingError::ProgramPaused);
+        require!(
+            !ctx.accounts.reward_stream.is_closed(Clock::get()?.unix_timestamp),
+            StakingError::RewardStreamClosing
+        );
+        
+        let vault_balance_before = ctx.accounts.stream_vault.amount;
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.stream_vault.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+            },
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+        
+        // Credit what actually arrived, which is less than `amount` for transfer-fee mints
+        let amount = received_amount(&mut ctx.accounts.stream_vault, vault_balance_before)?;
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.total_balance = reward_stream.total_balance.checked_add(amount).unwrap();
+        
+        emit!(RewardStreamFundedEvent {
+            stream_id,
+            funder: ctx.accounts.funder.key(),
+            amount,
+            total_balance: reward_stream.total_balance,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn join_reward_stream(
+        ctx: Context<JoinRewardStream>,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_STAKE), StakingError::ProgramPaused);
+        let clock = Clock::get()?;
+        require!(ctx.accounts.reward_stream.closes_at == 0, StakingError::RewardStreamClosing);
+        
+        // Only stake-seconds from here on count towards this stream
+        let user_stake = &mut ctx.accounts.user_stake;
+        accrue_stake_seconds(user_stake, clock.unix_timestamp);
+        user_stake.stream_positions = user_stake.stream_positions.checked_add(1).unwrap();
+        
+        let stream_position = &mut ctx.accounts.stream_position;
+        stream_position.owner = ctx.accounts.user.key();
+        stream_position.position_id = position_id;
+        stream_position.stake_seconds_checkpoint = user_stake.tier_stake_seconds;
+        stream_position.rewards_earned = 0;
+        stream_position.rewards_claimed = 0;
+        stream_position.bump = ctx.bumps.stream_position;
+        stream_position.settled_at = clock.unix_timestamp;
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.participants = reward_stream.participants.checked_add(1).unwrap();
+        
+        emit!(RewardStreamJoinedEvent {
+            stream_id,
+            user: ctx.accounts.user.key(),
+            position_id,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn claim_reward_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRewardStream<'info>>,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIM), StakingError::ProgramPaused);
+        let clock = Clock::get()?;
+        
+        // Settle stake-seconds up to now (or the stream's close) and price them at the stream's rates
+        let stream_position = &mut ctx.accounts.stream_position;
+        accrue_stream_rewards(
+            &ctx.accounts.reward_stream,
+            &mut ctx.accounts.user_stake,
+            stream_position,
+            clock.unix_timestamp,
+        )?;
+        
+        let claimable = stream_position.rewards_earned
+            .checked_sub(stream_position.rewards_claimed).unwrap();
+        require!(claimable > 0, StakingError::NoRewards);
+        require!(
+            ctx.accounts.reward_stream.total_balance >= claimable,
+            StakingError::InsufficientEscrowBalance
+        );
+        
+        let program_state_key = ctx.accounts.program_state.key();
+        let stream_id_bytes = stream_id.to_le_bytes();
+        let stream_seeds = &[
+            b"reward-stream",
+            program_state_key.as_ref(),
+            stream_id_bytes.as_ref(),
+            &[ctx.accounts.reward_stream.bump],
+        ];
+        let stream_signer = &[&stream_seeds[..]];
+        
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.stream_vault.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.reward_stream.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+            },
+            stream_signer,
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_checked_with_hook(transfer_ctx, claimable, ctx.accounts.reward_mint.decimals)?;
+        
+        let stream_position = &mut ctx.accounts.stream_position;
+        stream_position.rewards_claimed = stream_position.rewards_earned;
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.total_balance = reward_stream.total_balance.checked_sub(claimable).unwrap();
+        reward_stream.total_distributed = reward_stream.total_distributed.checked_add(claimable).unwrap();
+        
+        emit!(RewardStreamClaimedEvent {
+            stream_id,
+            user: ctx.accounts.user.key(),
+            position_id,
+            amount: claimable,
+            total_distributed: reward_stream.total_distributed,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn leave_reward_stream(
+        ctx: Context<LeaveRewardStream>,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<()> {
+        // Enforce pause
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_UNSTAKE), StakingError::ProgramPaused);
+        let clock = Clock::get()?;
+        
+        // Whatever has not been claimed is forfeited along with the stream position
+        let stream_position = &mut ctx.accounts.stream_position;
+        accrue_stream_rewards(
+            &ctx.accounts.reward_stream,
+            &mut ctx.accounts.user_stake,
+            stream_position,
+            clock.unix_timestamp,
+        )?;
+        let forfeited = stream_position.rewards_earned
+            .checked_sub(stream_position.rewards_claimed).unwrap();
+        
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.stream_positions = user_stake.stream_positions.checked_sub(1).unwrap();
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.participants = reward_stream.participants.checked_sub(1).unwrap();
+        
+        emit!(RewardStreamLeftEvent {
+            stream_id,
+            user: ctx.accounts.user.key(),
+            position_id,
+            forfeited,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    // Permissionless once the stream has closed: pays a position what it earned up to the close
+    // and removes it from the stream, so the treasurer can sweep the vault without taking rewards
+    // that are still owed
+    pub fn settle_reward_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleRewardStream<'info>>,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.program_state.is_paused(PAUSE_CLAIM), StakingError::ProgramPaused);
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.reward_stream.is_closed(clock.unix_timestamp),
+            StakingError::RewardStreamNotClosing
+        );
+        
+        let stream_position = &mut ctx.accounts.stream_position;
+        accrue_stream_rewards(
+            &ctx.accounts.reward_stream,
+            &mut ctx.accounts.user_stake,
+            stream_position,
+            clock.unix_timestamp,
+        )?;
+        let claimable = stream_position.rewards_earned
+            .checked_sub(stream_position.rewards_claimed).unwrap();
+        require!(
+            ctx.accounts.reward_stream.total_balance >= claimable,
+            StakingError::InsufficientEscrowBalance
+        );
+        
+        if claimable > 0 {
+            let program_state_key = ctx.accounts.program_state.key();
+            let stream_id_bytes = stream_id.to_le_bytes();
+            let stream_seeds = &[
+                b"reward-stream",
+                program_state_key.as_ref(),
+                stream_id_bytes.as_ref(),
+                &[ctx.accounts.reward_stream.bump],
+            ];
+            let stream_signer = &[&stream_seeds[..]];
+            
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stream_vault.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_stream.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                },
+                stream_signer,
+            ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, claimable, ctx.accounts.reward_mint.decimals)?;
+        }
+        
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.stream_positions = user_stake.stream_positions.checked_sub(1).unwrap();
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.total_balance = reward_stream.total_balance.checked_sub(claimable).unwrap();
+        reward_stream.total_distributed = reward_stream.total_distributed.checked_add(claimable).unwrap();
+        reward_stream.participants = reward_stream.participants.checked_sub(1).unwrap();
+        
+        emit!(RewardStreamSettledEvent {
+            stream_id,
+            owner: ctx.accounts.owner.key(),
+            position_id,
+            amount: claimable,
+            caller: ctx.accounts.caller.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn propose_reward_stream_close(
+        ctx: Context<ManageRewardStream>,
+        stream_id: u64,
+    ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        
+        // Participants keep earning and can claim until the stream closes
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        require!(reward_stream.closes_at == 0, StakingError::RewardStreamClosing);
+        reward_stream.closes_at = timestamp + ADMIN_TIMELOCK_DURATION;
+        
+        emit!(RewardStreamCloseProposedEvent {
+            stream_id,
+            authority: ctx.accounts.authority.key(),
+            closes_at: reward_stream.closes_at,
+            timestamp,
+        });
+        
+        Ok(())
+    }
+    
+    pub fn close_reward_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseRewardStream<'info>>,
+        stream_id: u64,
+    ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.reward_stream.closes_at > 0, StakingError::RewardStreamNotClosing);
+        require!(
+            ctx.accounts.reward_stream.is_closed(timestamp),
+            StakingError::TimelockNotExpired
+        );
+        
+        // Open positions may still be owed rewards; settle_reward_stream pays them out first
+        require!(
+            ctx.accounts.reward_stream.participants == 0,
+            StakingError::RewardStreamParticipantsRemaining
+        );
+        
+        // Nothing is owed anymore, so sweep the whole vault, including anything sent to it
+        // outside fund_reward_stream
+        let amount = ctx.accounts.stream_vault.amount;
+        if amount > 0 {
+            let program_state_key = ctx.accounts.program_state.key();
+            let stream_id_bytes = stream_id.to_le_bytes();
+            let stream_seeds = &[
+                b"reward-stream",
+                program_state_key.as_ref(),
+                stream_id_bytes.as_ref(),
+                &[ctx.accounts.reward_stream.bump],
+            ];
+            let stream_signer = &[&stream_seeds[..]];
+            
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.stream_vault.to_account_info(),
+                    to: ctx.accounts.destination_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_stream.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                },
+                stream_signer,
+            ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+            transfer_checked_with_hook(transfer_ctx, amount, ctx.accounts.reward_mint.decimals)?;
+        }
+        
+        let reward_stream = &mut ctx.accounts.reward_stream;
+        reward_stream.total_balance = 0;
+        
+        emit!(RewardStreamClosedEvent {
+            stream_id,
+            authority: ctx.accounts.authority.key(),
+            destination: ctx.accounts.destination_token_account.key(),
+            amount,
+            timestamp,
+        });
+        
+        Ok(())
+    }
+}
+
+// Account structures
+
+// Program-wide settings. Only the global admin may create pools, so nobody can claim a
+// mint's pool PDA before the intended authority does.
+#[account]
+pub struct GlobalConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+}
+
+impl GlobalConfig {
+    pub const SPACE: usize = 8 + 32 + 1;
+}
+
+// State of one staking pool, seeded by its staking mint. Vaults, escrow, configs and user
+// positions are all derived from this account's address.
+#[account]
+pub struct ProgramState {
+    pub authority: Pubkey,
+    pub defai_mint: Pubkey,
+    pub total_staked: u64,
+    pub total_users: u64,
+    pub pause_flags: u8,             // PAUSE_* bits for the operations currently paused
+    pub vault_bump: u8,              // Bump for stake-vault PDA
+    pub reward_escrow_bump: u8,      // Bump for reward-escrow PDA
+    pub escrow_vault_bump: u8,       // Bump for escrow-vault PDA (token account)
+    pub pending_authority: Option<Pubkey>,
+    pub authority_change_timestamp: i64,
+    pub unbonding_period: i64,       // Cooldown between request_unstake and complete_unstake
+    pub total_unbonding: u64,        // Held in stake-vault but excluded from total_staked
+    pub auto_pause_on_deficit: bool, // Let check_invariants pause the program on a vault deficit
+    pub paused_at: i64,              // When the current (or last) unstake pause started
+    pub emergency_grace_period: i64, // Pause duration after which emergency_withdraw opens
+    pub funder_allowlist_enabled: bool, // Restrict fund_escrow to approved Funder records
+    pub treasury: Pubkey,            // Token account receiving the treasury share of penalties
+    pub next_stream_id: u64,         // Id of the next secondary reward stream
+    pub unstake_resumed_at: i64,     // When unstaking was last unpaused
+}
+
+impl ProgramState {
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.pause_flags & flag != 0
+    }
+}
+
+#[account]
+pub struct RewardEscrow {
+    pub authority: Pubkey,
+    pub total_balance: u64,
+    pub total_distributed: u64,
+    pub bump: u8,
+    pub pending_withdrawal_amount: u64,
+    pub pending_withdrawal_destination: Option<Pubkey>, // Token account receiving the surplus
+    pub withdrawal_unlock_timestamp: i64,
+}
+
+#[account]
+pub struct AdminRoles {
+    pub members: Vec<RoleAssignment>,
+    pub bump: u8,
+}
+
+impl AdminRoles {
+    pub const SPACE: usize = 8 + 4 + MAX_ROLE_MEMBERS * RoleAssignment::SIZE + 1;
+    
+    pub fn has_role(&self, key: &Pubkey, role: u8) -> bool {
+        self.members
+            .iter()
+            .any(|m| m.member == *key && m.roles & role == role)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RoleAssignment {
+    pub member: Pubkey,
+    pub roles: u8,
+}
+
+impl RoleAssignment {
+    pub const SIZE: usize = 32 + 1;
+}
+
+#[account]
+pub struct AdminCouncil {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,                // Approvals needed before the timelock starts
+    pub proposal_count: u64,          // Next proposal id
+    pub epoch: u32,                   // Bumped on signer changes to invalidate open proposals
+    pub bump: u8,
+}
+
+impl AdminCouncil {
+    pub const SPACE: usize = 8 + 4 + MAX_COUNCIL_SIGNERS * 32 + 1 + 8 + 4 + 1;
+    
+    pub fn is_signer(&self, key: &Pubkey) -> bool {
+        self.signers.contains(key)
+    }
+}
+
+#[account]
+pub struct AdminProposal {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: AdminAction,
+    pub approvals: Vec<Pubkey>,
+    pub council_epoch: u32,
+    pub created_at: i64,
+    pub threshold_reached_at: i64,    // 0 until enough signers have approved
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl AdminProposal {
+    pub const SPACE: usize = 8 + 8 + 32
+        + AdminAction::MAX_SIZE
+        + 4 + MAX_COUNCIL_SIGNERS * 32  // approvals
+        + 4 + 8 + 8 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AdminAction {
+    SetPauseFlags { pause_flags: u8 },
+    ChangeAuthority { new_authority: Pubkey },
+    AcceptAuthority,                  // Take over a program authority proposed to the council
+    UpdateTierConfig {
+        tiers: Vec<TierInfo>,
+        lock_terms: Vec<LockTerm>,
+        base_apy_bps: u16,
+    },
+    WithdrawEscrow { amount: u64, destination: Pubkey },
+    UpdatePenaltySchedule {
+        brackets: Vec<PenaltyBracket>,
+        linear_decay: bool,
+        split: PenaltySplit,
+    },
+    UpdateCouncil { signers: Vec<Pubkey>, threshold: u8 },
+    GrantRole { member: Pubkey, roles: u8 },
+    RevokeRole { member: Pubkey, roles: u8 },
+    SetTreasury { treasury: Pubkey },
+    CancelAuthorityChange,            // Withdraw an authority handover the council proposed
+}
+
+impl AdminAction {
+    // Variant tag plus the largest variant (UpdateTierConfig)
+    pub const MAX_SIZE: usize = 1
+        + 4 + MAX_TIERS * TierInfo::SIZE
+        + 4 + MAX_LOCK_TERMS * LockTerm::SIZE
+        + 2;
+}
+
+#[account]
+pub struct Funder {
+    pub funder: Pubkey,
+    pub approved: bool,
+    pub total_contributed: u64,       // Cumulative escrow funding from this funder
+    pub last_funded_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct TierConfig {
+    pub tiers: Vec<TierInfo>,                  // Ordered by min_amount, contiguous ranges
+    pub lock_terms: Vec<LockTerm>,             // Ordered by duration_days
+    pub base_apy_bps: u16,                     // APY for stakes below the lowest tier (tier 0)
+    pub pending_tiers: Option<Vec<TierInfo>>,
+    pub pending_lock_terms: Option<Vec<LockTerm>>,
+    pub pending_base_apy_bps: u16,
+    pub tier_change_timestamp: i64,
+    pub bump: u8,
+    pub min_runway_days: u16,                  // Escrow runway below which APY accrual is scaled, 0 = off
+}
+
+impl TierConfig {
+    pub const SPACE: usize = 8
+        + 4 + MAX_TIERS * TierInfo::SIZE        // tiers
+        + 4 + MAX_LOCK_TERMS * LockTerm::SIZE   // lock_terms
+        + 2                                     // base_apy_bps
+        + 1 + 4 + MAX_TIERS * TierInfo::SIZE    // pending_tiers
+        + 1 + 4 + MAX_LOCK_TERMS * LockTerm::SIZE // pending_lock_terms
+        + 2                                     // pending_base_apy_bps
+        + 8
+        + 1
+        + 2;                                    // min_runway_days
+}
+
+// Running reward state derived from the TierConfig rates, updated whenever stake or time moves
+#[account]
+pub struct RewardAccrual {
+    pub reward_indices: [u128; MAX_TIERS + 1], // Cumulative reward per staked token, keyed by tier id
+    pub index_remainders: [u128; MAX_TIERS + 1], // Index growth below 1 unit, carried between updates
+    pub last_index_update: i64,
+    pub tier_weights: [u128; MAX_TIERS + 1],   // Sum of staked_amount * apy_multiplier_bps per tier
+    pub accrued_liabilities: u64,              // Rewards accrued to stakers but not yet paid out
+    pub bonus_per_share: u128,                 // Cumulative redistributed penalties per staked token
+    pub campaigns: Vec<RewardCampaign>,        // Live campaigns, dropped once ended or out of budget
+    pub next_campaign_id: u64,
+    pub reward_scale_bps: u16,                 // Share of tier APY currently accruing, 10000 = full
+    pub bump: u8,
+}
+
+impl RewardAccrual {
+    pub const SPACE: usize = 8
+        + 16 * (MAX_TIERS + 1) * 2 + 8          // reward_indices, index_remainders, last_index_update
+        + 16 * (MAX_TIERS + 1) + 8              // tier_weights, accrued_liabilities
+        + 16                                    // bonus_per_share
+        + 4 + MAX_CAMPAIGNS * RewardCampaign::SIZE // campaigns
+        + 8                                     // next_campaign_id
+        + 2                                     // reward_scale_bps
+        + 1;
+    
+    // Accrued rewards plus the unspent budget of live campaigns, none of which may leave the escrow
+    pub fn committed_rewards(&self) -> u64 {
+        self.campaigns
+            .iter()
+            .map(|campaign| campaign.total_budget.saturating_sub(campaign.distributed))
+            .fold(self.accrued_liabilities, |total, remaining| total.saturating_add(remaining))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TierInfo {
+    pub id: u8,                 // 1..=MAX_TIERS, 0 is reserved for "no tier"
+    pub name: String,
+    pub min_amount: u64,
+    pub max_amount: u64,        // Inclusive
+    pub apy_bps: u16,
+}
+
+impl TierInfo {
+    pub const SIZE: usize = 1 + 4 + MAX_TIER_NAME_LEN + 8 + 8 + 2;
+}
+
+// Time-bounded promo emitting a fixed amount per second, on top of tier APY, to the stakes of the
+// eligible tiers pro-rata to their reward weight. Paid from the reward escrow.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RewardCampaign {
+    pub campaign_id: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub eligible_tiers: u16,    // Bit per tier id, bit 0 = stakes below the lowest tier
+    pub emission_per_second: u64,
+    pub total_budget: u64,      // Reserved in the escrow at creation
+    pub distributed: u64,       // Emitted into the tier indices so far
+}
+
+impl RewardCampaign {
+    pub const SIZE: usize = 8 + 8 + 8 + 2 + 8 + 8 + 8;
+    
+    pub fn is_eligible(&self, tier_id: usize) -> bool {
+        self.eligible_tiers & (1 << tier_id) != 0
+    }
+}
+
+#[account]
+pub struct PenaltyConfig {
+    pub brackets: Vec<PenaltyBracket>,         // Ordered by min_days, first bracket starts at day 0
+    pub linear_decay: bool,                    // Interpolate between brackets instead of stepping
+    pub pending_brackets: Option<Vec<PenaltyBracket>>,
+    pub pending_linear_decay: bool,
+    pub penalty_change_timestamp: i64,
+    pub bump: u8,
+    pub split: PenaltySplit,                   // Where collected penalties are routed
+    pub pending_split: PenaltySplit,
+    pub distribute_to_stakers: bool,           // Pay the escrow share out pro-rata as a bonus
+}
+
+impl PenaltyConfig {
+    pub const SPACE: usize = 8
+        + 4 + MAX_PENALTY_BRACKETS * PenaltyBracket::SIZE      // brackets
+        + 1
+        + 1 + 4 + MAX_PENALTY_BRACKETS * PenaltyBracket::SIZE  // pending_brackets
+        + 1
+        + 8
+        + 1
+        + PenaltySplit::SIZE * 2
+        + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PenaltySplit {
+    pub escrow_bps: u16,
+    pub treasury_bps: u16,
+    pub burn_bps: u16,
+}
+
+impl PenaltySplit {
+    pub const SIZE: usize = 2 + 2 + 2;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PenaltyBracket {
+    pub min_days: u16,          // Applies from this many days since the last stake
+    pub penalty_bps: u16,
+}
+
+impl PenaltyBracket {
+    pub const SIZE: usize = 2 + 2;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LockTerm {
+    pub duration_days: u16,
+    pub multiplier_bps: u16,    // Applied to the tier APY, 10_000 = 1x
+}
+
+impl LockTerm {
+    pub const SIZE: usize = 2 + 2;
+}
+
+#[account]
+pub struct PositionCounter {
+    pub owner: Pubkey,
+    pub next_position_id: u64,
+    pub open_positions: u64,
+    pub total_staked: u64,            // Sum across all of the wallet's positions
+    pub aggregate_tier: u8,           // Tier of total_staked, used for card eligibility
+    pub bump: u8,
+    pub next_ticket_id: u64,
+}
+
+#[account]
+pub struct UnbondingTicket {
+    pub owner: Pubkey,
+    pub ticket_id: u64,
+    pub position_id: u64,             // Position the amount was taken from
+    pub amount: u64,
+    pub requested_at: i64,
+    pub claimable_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct UserStake {
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub staked_amount: u64,
+    pub rewards_earned: u64,
+    pub rewards_claimed: u64,
+    pub tier: u8,
+    pub stake_timestamp: i64,         // Initial stake timestamp
+    pub last_stake_timestamp: i64,    // Most recent stake timestamp for penalty calculation
+    pub last_claim_timestamp: i64,
+    pub locked_until: i64,
+    pub reward_index_checkpoint: u128, // Tier reward index at last settlement
+    pub lock_days: u16,                // Selected lock term, 0 = flexible
+    pub apy_multiplier_bps: u16,       // Snapshot of the term multiplier at stake/extend time
+    pub reward_weight: u128,           // Contribution to RewardAccrual.tier_weights
+    pub bonus_checkpoint: u128,        // RewardAccrual.bonus_per_share at last settlement
+    pub bonus_earned: u64,             // Redistributed penalties credited to this position
+    pub bonus_claimed: u64,
+    pub tier_stake_seconds: [u128; MAX_TIERS + 1], // Cumulative staked_amount * seconds, keyed by tier id
+    pub stake_seconds_updated_at: i64,
+    pub stream_positions: u8,          // Open StreamPosition accounts; the stake cannot close until they are left
+    pub open_tickets: u8,              // Outstanding UnbondingTickets; the stake cannot close until they complete
+}
+
+// Secondary reward paid in another mint (e.g. USDC cashback) from its own vault. Rates are fixed at
+// creation because unclaimed stake-seconds are priced at claim time; change them with a new stream.
+// Accrual is not bounded by the funded balance: claims are paid first come, first served from the
+// vault. Nothing accrues past `closes_at`, and the vault can only be swept once every position has
+// left or been settled, so rewards earned before the close stay claimable.
+#[account]
+pub struct RewardStream {
+    pub stream_id: u64,
+    pub reward_mint: Pubkey,
+    pub authority: Pubkey,              // Creator of the stream
+    pub tier_rates: [u64; MAX_TIERS + 1], // Reward units per staked unit per year, scaled by REWARD_INDEX_PRECISION
+    pub total_balance: u64,             // Funded and not yet paid out
+    pub total_distributed: u64,
+    pub created_at: i64,
+    pub bump: u8,
+    pub vault_bump: u8,                 // Bump for stream-vault PDA (token account)
+    pub closes_at: i64,                 // When accrual stops, 0 = open
+    pub participants: u64,              // Open StreamPositions; the vault is swept only once none are left
+}
+
+impl RewardStream {
+    pub const SPACE: usize = 8 + 8 + 32 + 32 + 8 * (MAX_TIERS + 1) + 8 + 8 + 8 + 1 + 1 + 8 + 8;
+    
+    pub fn is_closed(&self, current_timestamp: i64) -> bool {
+        self.closes_at > 0 && current_timestamp >= self.closes_at
+    }
+}
+
+// A position's participation in one reward stream
+#[account]
+pub struct StreamPosition {
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub stake_seconds_checkpoint: [u128; MAX_TIERS + 1], // UserStake.tier_stake_seconds at last settlement
+    pub rewards_earned: u64,
+    pub rewards_claimed: u64,
+    pub bump: u8,
+    pub settled_at: i64,                // Time the checkpoint was taken, capped at the stream's close
+}
+
+impl StreamPosition {
+    pub const SPACE: usize = 8 + 32 + 8 + 16 * (MAX_TIERS + 1) + 8 + 8 + 1 + 8;
+}
+
+// Context structs
+#[derive(Accounts)]
+pub struct InitializeGlobalConfig<'info> {
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = GlobalConfig::SPACE,
+        seeds = [b"global-config"],
+        bump
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ StakingError::InvalidAuthority
+    )]
+    pub program: Program<'info, crate::program::DefaiStaking>,
+    
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ StakingError::InvalidAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGlobalAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"global-config"],
+        bump = global_config.bump,
+        has_one = admin @ StakingError::InvalidAuthority
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgram<'info> {
+    #[account(
+        seeds = [b"global-config"],
+        bump = global_config.bump,
+        constraint = global_config.admin == authority.key() @ StakingError::InvalidAuthority
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 1 + 33 + 8 + 8 + 8 + 1 + 8 + 8 + 1 + 32 + 8 + 8,  // Added 8 bytes for unstake_resumed_at
+        // One pool per staking mint
+        seeds = [b"program-state", defai_mint.key().as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump,
+        token::mint = defai_mint,
+        token::authority = stake_vault,
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    // Created with the pool so admin instructions, pausing included, work from the start
+    #[account(
+        init,
+        payer = authority,
+        space = AdminRoles::SPACE,
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 1 + 8 + 33 + 8,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump,
+        token::mint = defai_mint,
+        token::authority = reward_escrow,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTierConfig<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = TierConfig::SPACE,
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = RewardAccrual::SPACE,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePenaltyConfig<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = PenaltyConfig::SPACE,
+        seeds = [b"penalty-config", program_state.key().as_ref()],
+        bump
+    )]
+    pub penalty_config: Account<'info, PenaltyConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRoles<'info> {
+    // Only the owner manages roles
+    #[account(
+        has_one = authority @ StakingError::InvalidAuthority
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminCouncil<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = AdminCouncil::SPACE,
+        seeds = [b"admin-council", program_state.key().as_ref()],
+        bump
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAdminProposal<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"admin-council", program_state.key().as_ref()],
+        bump = admin_council.bump,
+        constraint = admin_council.is_signer(&proposer.key()) @ StakingError::NotCouncilSigner
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = AdminProposal::SPACE,
+        seeds = [b"admin-proposal", admin_council.key().as_ref(), &admin_council.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub admin_proposal: Account<'info, AdminProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApproveAdminProposal<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-council", program_state.key().as_ref()],
+        bump = admin_council.bump,
+        constraint = admin_council.is_signer(&approver.key()) @ StakingError::NotCouncilSigner
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+    
+    #[account(
+        mut,
+        seeds = [b"admin-proposal", admin_council.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = admin_proposal.bump
+    )]
+    pub admin_proposal: Account<'info, AdminProposal>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteAdminProposal<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"admin-council", program_state.key().as_ref()],
+        bump = admin_council.bump,
+        constraint = admin_council.is_signer(&executor.key()) @ StakingError::NotCouncilSigner
+    )]
+    pub admin_council: Account<'info, AdminCouncil>,
+    
+    #[account(
+        mut,
+        seeds = [b"admin-proposal", admin_council.key().as_ref(), &proposal_id.to_le_bytes()],
+        bump = admin_proposal.bump
+    )]
+    pub admin_proposal: Account<'info, AdminProposal>,
+    
+    #[account(
+        mut,
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    // Only needed for UpdatePenaltySchedule
+    #[account(
+        mut,
+        seeds = [b"penalty-config", program_state.key().as_ref()],
+        bump = penalty_config.bump
+    )]
+    pub penalty_config: Option<Account<'info, PenaltyConfig>>,
+    
+    // Escrow accounts, only needed for WithdrawEscrow (UpdateTierConfig needs reward_escrow too)
+    #[account(
+        mut,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Option<Account<'info, RewardEscrow>>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump = program_state.escrow_vault_bump
+    )]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    // Only needed for GrantRole and RevokeRole
+    #[account(
+        mut,
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Option<Account<'info, AdminRoles>>,
+    
+    // Only needed for SetTreasury
+    #[account(
+        constraint = treasury_token_account.mint == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub executor: Signer<'info>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct FundEscrow<'info> {
+    // Bring in ProgramState to access authoritative addresses
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        // Ensure reward_escrow is the correct PDA
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        mut,
+        // Ensure escrow_token_account is the correct ATA and is owned by reward_escrow
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump = program_state.escrow_vault_bump,
+        token::authority = reward_escrow,
+        token::mint = defai_mint,  // Ensure the ATA's mint matches the provided mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = 8 + 32 + 1 + 8 + 8 + 1,
+        seeds = [b"funder", program_state.key().as_ref(), funder.key().as_ref()],
+        bump
+    )]
+    pub funder_record: Account<'info, Funder>,
+    
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    #[account(
+        // Ensure the provided mint is the official one from ProgramState
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(funder: Pubkey)]
+pub struct AddFunder<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 1 + 8 + 8 + 1,
+        seeds = [b"funder", program_state.key().as_ref(), funder.as_ref()],
+        bump
+    )]
+    pub funder_record: Account<'info, Funder>,
+    
+    #[account(
+        mut,
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(funder: Pubkey)]
+pub struct RemoveFunder<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        mut,
+        seeds = [b"funder", program_state.key().as_ref(), funder.as_ref()],
+        bump = funder_record.bump
+    )]
+    pub funder_record: Account<'info, Funder>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StakeTokens<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + 32 + 8 + 8 + 8 + 1 + 1 + 8,
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 16 + 2 + 2 + 16 + 16 + 8 + 8 + 16 * (MAX_TIERS + 1) + 8 + 1 + 1,  // Added open ticket count
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_counter.next_position_id.to_le_bytes()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump = program_state.vault_bump,
+        token::authority = stake_vault,
+        token::mint = defai_mint
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct UnstakeTokens<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"penalty-config", program_state.key().as_ref()],
+        bump = penalty_config.bump
+    )]
+    pub penalty_config: Account<'info, PenaltyConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump = program_state.vault_bump,
+        token::authority = stake_vault,
+        token::mint = defai_mint
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump = program_state.escrow_vault_bump,
+        token::authority = reward_escrow,
+        token::mint = defai_mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    // Only needed when the penalty split has a treasury share
+    #[account(
+        mut,
+        constraint = treasury_token_account.key() == program_state.treasury @ StakingError::InvalidTreasury
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    // Mutable so the burn share of a penalty can reduce supply
+    #[account(
+        mut,
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct RequestUnstake<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"unbonding-ticket", program_state.key().as_ref(), user.key().as_ref(), &position_counter.next_ticket_id.to_le_bytes()],
+        bump
+    )]
+    pub unbonding_ticket: Account<'info, UnbondingTicket>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_id: u64)]
+pub struct CompleteUnstake<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"unbonding-ticket", program_state.key().as_ref(), user.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump = unbonding_ticket.bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub unbonding_ticket: Account<'info, UnbondingTicket>,
+    
+    // The position the ticket was split from, which stays open until its tickets complete
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &unbonding_ticket.position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump = program_state.vault_bump,
+        token::authority = stake_vault,
+        token::mint = defai_mint
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(ticket_id: u64)]
+pub struct CancelUnstake<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"unbonding-ticket", program_state.key().as_ref(), user.key().as_ref(), &ticket_id.to_le_bytes()],
+        bump = unbonding_ticket.bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub unbonding_ticket: Account<'info, UnbondingTicket>,
+    
+    // The ticket is re-staked into the position it came from
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &unbonding_ticket.position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTreasuryConfig<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRewardStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = RewardStream::SPACE,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &program_state.next_stream_id.to_le_bytes()],
+        bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"stream-vault", reward_stream.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = reward_stream,
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64)]
+pub struct FundRewardStream<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream-vault", reward_stream.key().as_ref()],
+        bump = reward_stream.vault_bump,
+        token::authority = reward_stream,
+        token::mint = reward_mint,
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub funder: Signer<'info>,
+    
+    #[account(
+        constraint = reward_mint.key() == reward_stream.reward_mint @ StakingError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64, position_id: u64)]
+pub struct JoinRewardStream<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        init,
+        payer = user,
+        space = StreamPosition::SPACE,
+        seeds = [b"stream-position", reward_stream.key().as_ref(), user_stake.key().as_ref()],
+        bump
+    )]
+    pub stream_position: Account<'info, StreamPosition>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64, position_id: u64)]
+pub struct ClaimRewardStream<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream-vault", reward_stream.key().as_ref()],
+        bump = reward_stream.vault_bump,
+        token::authority = reward_stream,
+        token::mint = reward_mint,
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream-position", reward_stream.key().as_ref(), user_stake.key().as_ref()],
+        bump = stream_position.bump
+    )]
+    pub stream_position: Account<'info, StreamPosition>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == reward_stream.reward_mint @ StakingError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64, position_id: u64)]
+pub struct LeaveRewardStream<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"stream-position", reward_stream.key().as_ref(), user_stake.key().as_ref()],
+        bump = stream_position.bump
+    )]
+    pub stream_position: Account<'info, StreamPosition>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64, position_id: u64)]
+pub struct SettleRewardStream<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream-vault", reward_stream.key().as_ref()],
+        bump = reward_stream.vault_bump,
+        token::authority = reward_stream,
+        token::mint = reward_mint,
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), owner.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"stream-position", reward_stream.key().as_ref(), user_stake.key().as_ref()],
+        bump = stream_position.bump
+    )]
+    pub stream_position: Account<'info, StreamPosition>,
+    
+    // Rewards always go to the position owner, whoever cranks the settlement
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = reward_mint,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == reward_stream.reward_mint @ StakingError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    pub caller: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64)]
+pub struct ManageRewardStream<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream_id: u64)]
+pub struct CloseRewardStream<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-stream", program_state.key().as_ref(), &stream_id.to_le_bytes()],
+        bump = reward_stream.bump
+    )]
+    pub reward_stream: Account<'info, RewardStream>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream-vault", reward_stream.key().as_ref()],
+        bump = reward_stream.vault_bump,
+        token::authority = reward_stream,
+        token::mint = reward_mint,
+    )]
+    pub stream_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == reward_stream.reward_mint @ StakingError::InvalidMint
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = reward_mint.key() == reward_stream.reward_mint @ StakingError::InvalidMint
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetTreasury<'info> {
+    #[account(
+        mut,
+        has_one = authority @ StakingError::InvalidAuthority
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        constraint = treasury_token_account.mint == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_CONFIG_MANAGER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump = program_state.escrow_vault_bump,
+        token::authority = reward_escrow,
+        token::mint = defai_mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct CloseStake<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        mut,
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump = program_state.escrow_vault_bump,
+        token::authority = reward_escrow,
+        token::mint = defai_mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeEscrowWithdrawal<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteEscrowWithdrawal<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow-vault", program_state.key().as_ref()],
+        bump = program_state.escrow_vault_bump,
+        token::authority = reward_escrow,
+        token::mint = defai_mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = defai_mint
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
+    pub defai_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAuthority<'info> {
+    #[account(
+        mut,
+        has_one = authority @ StakingError::InvalidAuthority
+    )]
+    pub program_state: Account<'info, ProgramState>,
+    pub authority: Signer<'info>,
 }
 
-#[account]
-pub struct UserStake {
-    pub owner: Pubkey,
-    pub staked_amount: u64,
-    pub rewards_earned: u64,
-    pub rewards_claimed: u64,
-    pub tier: u8,
-    pub stake_timestamp: i64,         // Initial stake timestamp
-    pub last_stake_timestamp: i64,    // Most recent stake timestamp for penalty calculation
-    pub last_claim_timestamp: i64,
-    pub locked_until: i64,
+#[derive(Accounts)]
+pub struct UpdateTierConfig<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        mut,
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_CONFIG_MANAGER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
 }
 
-// Context structs
 #[derive(Accounts)]
-pub struct InitializeProgram<'info> {
+pub struct ManageRewardCampaign<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
     #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 8 + 8 + 1 + 1 + 1 + 1 + 33 + 8,  // Added 1 byte for escrow_vault_bump
-        seeds = [b"program-state"],
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshRunway<'info> {
+    #[account(
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
         bump
     )]
     pub program_state: Account<'info, ProgramState>,
     
     #[account(
-        init,
-        payer = authority,
-        seeds = [b"stake-vault", program_state.key().as_ref()],
-        bump,
-        token::mint = defai_mint,
-        token::authority = stake_vault,
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
     )]
-    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    pub tier_config: Account<'info, TierConfig>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
     
-    pub defai_mint: InterfaceAccount<'info, Mint>,
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeEscrow<'info> {
+pub struct UpdatePenaltyConfig<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
+    
+    #[account(
+        mut,
+        seeds = [b"penalty-config", program_state.key().as_ref()],
+        bump = penalty_config.bump
+    )]
+    pub penalty_config: Account<'info, PenaltyConfig>,
+    
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_CONFIG_MANAGER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CheckInvariants<'info> {
     #[account(
         mut,
-        seeds = [b"program-state"],
+        seeds = [b"program-state", program_state.defai_mint.as_ref()],
         bump
     )]
     pub program_state: Account<'info, ProgramState>,
     
     #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 8 + 8 + 1,
         seeds = [b"reward-escrow", program_state.key().as_ref()],
-        bump
+        bump = program_state.reward_escrow_bump
     )]
     pub reward_escrow: Account<'info, RewardEscrow>,
     
     #[account(
-        init,
-        payer = authority,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump = program_state.vault_bump,
+        token::authority = stake_vault,
+        token::mint = defai_mint
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
         seeds = [b"escrow-vault", program_state.key().as_ref()],
-        bump,
-        token::mint = defai_mint,
+        bump = program_state.escrow_vault_bump,
         token::authority = reward_escrow,
+        token::mint = defai_mint
     )]
     pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
+    #[account(
+        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+    )]
     pub defai_mint: InterfaceAccount<'info, Mint>,
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    
+    // Anyone can run the check
+    pub caller: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct FundEscrow<'info> {
-    // Bring in ProgramState to access authoritative addresses
+pub struct SyncEscrowBalance<'info> {
+    pub program_state: Account<'info, ProgramState>,
+    
     #[account(
-        seeds = [b"program-state"],
-        bump
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
     )]
-    pub program_state: Account<'info, ProgramState>,
+    pub admin_roles: Account<'info, AdminRoles>,
     
     #[account(
         mut,
-        // Ensure reward_escrow is the correct PDA
         seeds = [b"reward-escrow", program_state.key().as_ref()],
         bump = program_state.reward_escrow_bump
     )]
     pub reward_escrow: Account<'info, RewardEscrow>,
     
     #[account(
-        mut,
-        // Ensure escrow_token_account is the correct ATA and is owned by reward_escrow
         seeds = [b"escrow-vault", program_state.key().as_ref()],
         bump = program_state.escrow_vault_bump,
         token::authority = reward_escrow,
-        token::mint = defai_mint,  // Ensure the ATA's mint matches the provided mint
+        token::mint = defai_mint
     )]
     pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub funder: Signer<'info>,
-    
     #[account(
-        // Ensure the provided mint is the official one from ProgramState
         constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
     )]
     pub defai_mint: InterfaceAccount<'info, Mint>,
     
-    pub token_program: Interface<'info, TokenInterface>,
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_TREASURER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct StakeTokens<'info> {
-    #[account(mut)]
+#[instruction(position_id: u64)]
+pub struct ExtendLock<'info> {
     pub program_state: Account<'info, ProgramState>,
     
     #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + 32 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8,  // Added 8 bytes for last_stake_timestamp
-        seeds = [b"user-stake", user.key().as_ref()],
-        bump
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
     )]
-    pub user_stake: Account<'info, UserStake>,
+    pub tier_config: Account<'info, TierConfig>,
     
     #[account(
         mut,
-        seeds = [b"stake-vault", program_state.key().as_ref()],
-        bump = program_state.vault_bump,
-        token::authority = stake_vault,
-        token::mint = defai_mint
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
     )]
-    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    pub reward_accrual: Account<'info, RewardAccrual>,
     
-    #[account(mut)]
-    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
     
     #[account(
-        constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
     )]
-    pub defai_mint: InterfaceAccount<'info, Mint>,
+    pub user_stake: Account<'info, UserStake>,
     
-    #[account(mut)]
     pub user: Signer<'info>,
-    
-    pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
+    pub owner: SystemAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UnstakeTokens<'info> {
-    #[account(mut)]
+#[instruction(position_id: u64)]
+pub struct RetierPosition<'info> {
     pub program_state: Account<'info, ProgramState>,
     
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        seeds = [b"reward-escrow", program_state.key().as_ref()],
+        bump = program_state.reward_escrow_bump
+    )]
+    pub reward_escrow: Account<'info, RewardEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"position-counter", program_state.key().as_ref(), owner.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
     #[account(
         mut,
-        seeds = [b"user-stake", user.key().as_ref()],
+        seeds = [b"user-stake", program_state.key().as_ref(), owner.key().as_ref(), &position_id.to_le_bytes()],
         bump,
         has_one = owner @ StakingError::InvalidOwner
     )]
     pub user_stake: Account<'info, UserStake>,
     
+    pub owner: SystemAccount<'info>,
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
     #[account(
         mut,
-        seeds = [b"stake-vault", program_state.key().as_ref()],
-        bump = program_state.vault_bump,
-        token::authority = stake_vault,
-        token::mint = defai_mint
+        // The incoming authority signs, proving the proposed key is controlled
+        constraint = program_state.pending_authority == Some(new_authority.key()) @ StakingError::InvalidAuthority
     )]
-    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    pub program_state: Account<'info, ProgramState>,
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseProgram<'info> {
+    #[account(mut)]
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"admin-roles", program_state.key().as_ref()],
+        bump = admin_roles.bump
+    )]
+    pub admin_roles: Account<'info, AdminRoles>,
     
+    #[account(
+        constraint = is_authorized(&program_state, &admin_roles, &authority.key(), ROLE_PAUSER) @ StakingError::InvalidAuthority
+    )]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct EmergencyWithdraw<'info> {
     #[account(mut)]
-    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub program_state: Account<'info, ProgramState>,
+    
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
     
     #[account(
         mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
         seeds = [b"reward-escrow", program_state.key().as_ref()],
         bump = program_state.reward_escrow_bump
     )]
@@ -712,19 +4806,36 @@ pub struct UnstakeTokens<'info> {
     
     #[account(
         mut,
-        seeds = [b"escrow-vault", program_state.key().as_ref()],
-        bump = program_state.escrow_vault_bump,
-        token::authority = reward_escrow,
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
+        bump,
+        has_one = owner @ StakingError::InvalidOwner
+    )]
+    pub user_stake: Account<'info, UserStake>,
+    
+    #[account(
+        mut,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump = program_state.vault_bump,
+        token::authority = stake_vault,
         token::mint = defai_mint
     )]
-    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
     )]
     pub defai_mint: InterfaceAccount<'info, Mint>,
     
-    #[account(mut)]
     pub user: Signer<'info>,
     pub owner: SystemAccount<'info>,
     
@@ -732,20 +4843,42 @@ pub struct UnstakeTokens<'info> {
 }
 
 #[derive(Accounts)]
-pub struct ClaimRewards<'info> {
+#[instruction(position_id: u64)]
+pub struct CompoundRewards<'info> {
     #[account(mut)]
     pub program_state: Account<'info, ProgramState>,
     
     #[account(
         mut,
-        seeds = [b"user-stake", user.key().as_ref()],
+        seeds = [b"position-counter", program_state.key().as_ref(), user.key().as_ref()],
+        bump = position_counter.bump
+    )]
+    pub position_counter: Account<'info, PositionCounter>,
+    
+    #[account(
+        mut,
+        seeds = [b"user-stake", program_state.key().as_ref(), user.key().as_ref(), &position_id.to_le_bytes()],
         bump,
         has_one = owner @ StakingError::InvalidOwner
     )]
     pub user_stake: Account<'info, UserStake>,
     
+    #[account(
+        seeds = [b"tier-config", program_state.key().as_ref()],
+        bump = tier_config.bump
+    )]
+    pub tier_config: Account<'info, TierConfig>,
+    
     #[account(
         mut,
+        seeds = [b"reward-accrual", program_state.key().as_ref()],
+        bump = reward_accrual.bump
+    )]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    
+    #[account(
+        mut,
+        // Add this constraint to ensure it's the official PDA
         seeds = [b"reward-escrow", program_state.key().as_ref()],
         bump = program_state.reward_escrow_bump
     )]
@@ -760,8 +4893,14 @@ pub struct ClaimRewards<'info> {
     )]
     pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
     
-    #[account(mut)]
-    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"stake-vault", program_state.key().as_ref()],
+        bump = program_state.vault_bump,
+        token::authority = stake_vault,
+        token::mint = defai_mint
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         constraint = defai_mint.key() == program_state.defai_mint @ StakingError::InvalidMint
@@ -774,104 +4913,432 @@ pub struct ClaimRewards<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
-#[derive(Accounts)]
-pub struct UpdateAuthority<'info> {
-    #[account(
-        mut,
-        has_one = authority @ StakingError::InvalidAuthority
-    )]
-    pub program_state: Account<'info, ProgramState>,
-    pub authority: Signer<'info>,
+// Events
+#[event]
+pub struct GlobalAdminUpdatedEvent {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct PauseProgram<'info> {
-    #[account(
-        mut,
-        has_one = authority @ StakingError::InvalidAuthority
-    )]
-    pub program_state: Account<'info, ProgramState>,
-    pub authority: Signer<'info>,
+#[event]
+pub struct PoolInitializedEvent {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub tier: u8,
+    pub lock_days: u16,
+    pub locked_until: i64,
+    pub total_staked: u64,
+    pub aggregate_tier: u8,
+}
+
+#[event]
+pub struct UnstakeEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub penalty: u64,
+    pub penalty_bps: u16,
+    pub penalty_to_escrow: u64,
+    pub penalty_to_treasury: u64,
+    pub penalty_burned: u64,
+    pub penalty_distributed: bool,   // Escrow share went to the staker bonus pool
+    pub remaining_stake: u64,
+    pub new_tier: u8,
+    pub aggregate_tier: u8,
+}
+
+#[event]
+pub struct UnstakeRequestedEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub ticket_id: u64,
+    pub amount: u64,
+    pub claimable_at: i64,
+    pub aggregate_tier: u8,
+}
+
+#[event]
+pub struct UnstakeCompletedEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub ticket_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct UnstakeCancelledEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub ticket_id: u64,
+    pub amount: u64,
+    pub new_tier: u8,
+}
+
+#[event]
+pub struct UnbondingPeriodUpdatedEvent {
+    pub authority: Pubkey,
+    pub unbonding_period: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimedEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub bonus_amount: u64,           // Part of amount paid from redistributed penalties
+    pub total_distributed: u64,
+}
+
+#[event]
+pub struct StakeClosedEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub rewards_paid: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowFundedEvent {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+    pub funder_total_contributed: u64,
+}
+
+#[event]
+pub struct FunderUpdatedEvent {
+    pub authority: Pubkey,
+    pub funder: Pubkey,
+    pub approved: bool,
+    pub total_contributed: u64,
+}
+
+#[event]
+pub struct EscrowWithdrawalProposedEvent {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub unlock_timestamp: i64,
+}
+
+#[event]
+pub struct EscrowWithdrawalCancelledEvent {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowWithdrawnEvent {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub remaining_balance: u64,
+    pub accrued_liabilities: u64,
+}
+
+#[event]
+pub struct RolesUpdatedEvent {
+    pub authority: Pubkey,
+    pub member: Pubkey,
+    pub roles: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminCouncilUpdatedEvent {
+    pub council: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+    pub epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposalCreatedEvent {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: AdminAction,
+    pub threshold_reached_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposalApprovedEvent {
+    pub proposal_id: u64,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub threshold_reached_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposalExecutedEvent {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub current_authority: Pubkey,
+    pub proposed_authority: Pubkey,
+    pub executable_at: i64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityChangeCancelledEvent {
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityUpdatedEvent {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierConfigUpdatedEvent {
+    pub authority: Pubkey,
+    pub tiers: Vec<TierInfo>,
+    pub lock_terms: Vec<LockTerm>,
+    pub base_apy_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TierConfigChangeCancelledEvent {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryUpdatedEvent {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyDistributionUpdatedEvent {
+    pub authority: Pubkey,
+    pub distribute_to_stakers: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BonusDistributedEvent {
+    pub amount: u64,
+    pub total_staked: u64,
+    pub bonus_per_share: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyScheduleProposedEvent {
+    pub authority: Pubkey,
+    pub brackets: Vec<PenaltyBracket>,
+    pub linear_decay: bool,
+    pub split: PenaltySplit,
+    pub executable_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyScheduleUpdatedEvent {
+    pub authority: Pubkey,
+    pub brackets: Vec<PenaltyBracket>,
+    pub linear_decay: bool,
+    pub split: PenaltySplit,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PenaltyScheduleChangeCancelledEvent {
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReconciliationEvent {
+    pub stake_vault_balance: u64,
+    pub total_staked: u64,
+    pub total_unbonding: u64,
+    pub stake_vault_delta: i128,     // Vault balance minus total_staked + total_unbonding
+    pub escrow_vault_balance: u64,
+    pub escrow_total_balance: u64,
+    pub escrow_delta: i128,          // Vault balance minus RewardEscrow.total_balance
+    pub deficit: bool,
+    pub pause_flags: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowSyncedEvent {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct LockExtendedEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub lock_days: u16,
+    pub apy_multiplier_bps: u16,
+    pub locked_until: i64,
+}
+
+#[event]
+pub struct ProgramPausedEvent {
+    pub authority: Pubkey,
+    pub pause_flags: u8,
+    pub previous_flags: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub rewards_forfeited: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsCompoundedEvent {
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub amount_compounded: u64,
+    pub new_stake_amount: u64,
+    pub old_tier: u8,
+    pub new_tier: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionRetieredEvent {
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub old_tier: u8,
+    pub new_tier: u8,
+    pub aggregate_tier: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RunwayWarningEvent {
+    pub escrow_balance: u64,
+    pub committed_rewards: u64,      // Accrued liabilities plus unspent campaign budgets
+    pub projected_liabilities: u64,  // Full-APY accrual over min_runway_days
+    pub runway_days: u64,            // How long the uncommitted escrow lasts at full APY
+    pub min_runway_days: u16,
+    pub reward_scale_bps: u16,
+    pub previous_scale_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardCampaignCreatedEvent {
+    pub authority: Pubkey,
+    pub campaign: RewardCampaign,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct CompoundRewards<'info> {
-    #[account(mut)]
-    pub program_state: Account<'info, ProgramState>,
-    
-    #[account(
-        mut,
-        seeds = [b"user-stake", user.key().as_ref()],
-        bump,
-        has_one = owner @ StakingError::InvalidOwner
-    )]
-    pub user_stake: Account<'info, UserStake>,
-    
-    #[account(
-        mut,
-        // Add this constraint to ensure it's the official PDA
-        seeds = [b"reward-escrow", program_state.key().as_ref()],
-        bump = program_state.reward_escrow_bump
-    )]
-    pub reward_escrow: Account<'info, RewardEscrow>,
-    
-    pub user: Signer<'info>,
-    pub owner: SystemAccount<'info>,
+#[event]
+pub struct RewardCampaignEndedEvent {
+    pub campaign_id: u64,
+    pub distributed: u64,
+    pub cancelled: bool,             // false when it ran to its end time or budget
+    pub timestamp: i64,
 }
 
-// Events
 #[event]
-pub struct StakeEvent {
-    pub user: Pubkey,
+pub struct RewardStreamCreatedEvent {
+    pub stream_id: u64,
+    pub reward_mint: Pubkey,
+    pub tier_rates: Vec<u64>,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardStreamFundedEvent {
+    pub stream_id: u64,
+    pub funder: Pubkey,
     pub amount: u64,
-    pub tier: u8,
-    pub total_staked: u64,
+    pub total_balance: u64,
 }
 
 #[event]
-pub struct UnstakeEvent {
+pub struct RewardStreamJoinedEvent {
+    pub stream_id: u64,
     pub user: Pubkey,
-    pub amount: u64,
-    pub penalty: u64,
-    pub remaining_stake: u64,
-    pub new_tier: u8,
+    pub position_id: u64,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct RewardsClaimedEvent {
+pub struct RewardStreamClaimedEvent {
+    pub stream_id: u64,
     pub user: Pubkey,
+    pub position_id: u64,
     pub amount: u64,
     pub total_distributed: u64,
 }
 
 #[event]
-pub struct EscrowFundedEvent {
-    pub funder: Pubkey,
-    pub amount: u64,
-    pub new_balance: u64,
+pub struct RewardStreamLeftEvent {
+    pub stream_id: u64,
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub forfeited: u64,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct AuthorityUpdatedEvent {
-    pub old_authority: Pubkey,
-    pub new_authority: Pubkey,
+pub struct RewardStreamSettledEvent {
+    pub stream_id: u64,
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub caller: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct ProgramPausedEvent {
+pub struct RewardStreamCloseProposedEvent {
+    pub stream_id: u64,
     pub authority: Pubkey,
-    pub paused: bool,
+    pub closes_at: i64,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct RewardsCompoundedEvent {
-    pub user: Pubkey,
-    pub amount_compounded: u64,
-    pub new_stake_amount: u64,
-    pub old_tier: u8,
-    pub new_tier: u8,
+pub struct RewardStreamClosedEvent {
+    pub stream_id: u64,
+    pub authority: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
     pub timestamp: i64,
 }
 
@@ -900,65 +5367,863 @@ pub enum StakingError {
     TimelockNotExpired,
     #[msg("Invalid mint address")]
     InvalidMint,
+    #[msg("Invalid tier configuration")]
+    InvalidTierConfig,
+    #[msg("No pending tier config change")]
+    NoPendingTierConfigChange,
+    #[msg("Invalid lock term")]
+    InvalidLockTerm,
+    #[msg("Unbonding cooldown has not elapsed")]
+    UnbondingNotComplete,
+    #[msg("Invalid unbonding period")]
+    InvalidUnbondingPeriod,
+    #[msg("No unaccounted escrow balance to sync")]
+    NoUnaccountedEscrowBalance,
+    #[msg("Stake must be fully withdrawn before closing")]
+    StakeNotEmpty,
+    #[msg("Emergency withdraw is only available after the pause grace period")]
+    EmergencyExitNotAvailable,
+    #[msg("Invalid emergency grace period")]
+    InvalidEmergencyGracePeriod,
+    #[msg("Funder is not approved")]
+    FunderNotApproved,
+    #[msg("No pending escrow withdrawal")]
+    NoPendingEscrowWithdrawal,
+    #[msg("Withdrawal exceeds escrow surplus over accrued rewards")]
+    WithdrawalExceedsSurplus,
+    #[msg("Invalid withdrawal destination")]
+    InvalidWithdrawalDestination,
+    #[msg("An authority change is already pending")]
+    AuthorityChangeAlreadyPending,
+    #[msg("Authority change proposal has expired")]
+    AuthorityProposalExpired,
+    #[msg("Invalid role")]
+    InvalidRole,
+    #[msg("Too many role members")]
+    TooManyRoleMembers,
+    #[msg("Invalid admin council signers or threshold")]
+    InvalidCouncilConfig,
+    #[msg("Signer is not a member of the admin council")]
+    NotCouncilSigner,
+    #[msg("Admin proposal already approved by this signer")]
+    ProposalAlreadyApproved,
+    #[msg("Admin proposal already executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Admin proposal has not reached the approval threshold")]
+    ProposalThresholdNotReached,
+    #[msg("Admin proposal predates a council change")]
+    StaleAdminProposal,
+    #[msg("Account required by the admin proposal is missing")]
+    MissingProposalAccount,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Invalid penalty schedule")]
+    InvalidPenaltySchedule,
+    #[msg("No pending penalty schedule change")]
+    NoPendingPenaltyChange,
+    #[msg("Penalty split must add up to 10000 basis points")]
+    InvalidPenaltySplit,
+    #[msg("Treasury account missing or not configured")]
+    InvalidTreasury,
+    #[msg("Invalid reward stream rates")]
+    InvalidRewardStream,
+    #[msg("Invalid reward campaign parameters")]
+    InvalidCampaign,
+    #[msg("Too many live reward campaigns")]
+    TooManyCampaigns,
+    #[msg("Reward campaign not found")]
+    CampaignNotFound,
+    #[msg("Minimum runway exceeds 365 days")]
+    InvalidRunwayConfig,
+    #[msg("Tier table update drops a tier that still has stake")]
+    StakedTierRemoved,
+    #[msg("Emergency grace period cannot change while unstaking is or was just paused")]
+    GracePeriodLocked,
+    #[msg("An escrow withdrawal is already pending")]
+    EscrowWithdrawalAlreadyPending,
+    #[msg("Leave all reward streams before closing the stake")]
+    StreamPositionsOpen,
+    #[msg("Reward stream is closing or closed")]
+    RewardStreamClosing,
+    #[msg("Reward stream close has not been proposed")]
+    RewardStreamNotClosing,
+    #[msg("A tier config change is already pending")]
+    TierConfigChangeAlreadyPending,
+    #[msg("A penalty schedule change is already pending")]
+    PenaltyChangeAlreadyPending,
+    #[msg("Complete or cancel all unbonding tickets before closing the stake")]
+    UnbondingTicketsOpen,
+    #[msg("Too many open unbonding tickets on this position")]
+    TooManyOpenTickets,
+    #[msg("Reward stream still has open positions")]
+    RewardStreamParticipantsRemaining,
 }
 
 // Helper functions
-fn get_tier(amount: u64) -> Result<u8> {
-    if amount >= INFINITE_MIN {
-        Ok(3) // Infinite
-    } else if amount >= TITANIUM_MIN {
-        Ok(2) // Titanium
-    } else if amount >= GOLD_MIN {
-        Ok(1) // Gold
-    } else {
-        Ok(0) // No tier
+fn default_tiers() -> Vec<TierInfo> {
+    vec![
+        TierInfo {
+            id: 1,
+            name: "Gold".to_string(),
+            min_amount: GOLD_MIN,
+            max_amount: TITANIUM_MIN - 1,
+            apy_bps: GOLD_APY_BPS,
+        },
+        TierInfo {
+            id: 2,
+            name: "Titanium".to_string(),
+            min_amount: TITANIUM_MIN,
+            max_amount: INFINITE_MIN - 1,
+            apy_bps: TITANIUM_APY_BPS,
+        },
+        TierInfo {
+            id: 3,
+            name: "Infinite".to_string(),
+            min_amount: INFINITE_MIN,
+            max_amount: u64::MAX,
+            apy_bps: INFINITE_APY_BPS,
+        },
+    ]
+}
+
+fn default_lock_terms() -> Vec<LockTerm> {
+    vec![
+        LockTerm { duration_days: 30, multiplier_bps: 11_000 },  // 1.1x
+        LockTerm { duration_days: 90, multiplier_bps: 12_500 },  // 1.25x
+        LockTerm { duration_days: 180, multiplier_bps: 15_000 }, // 1.5x
+        LockTerm { duration_days: 365, multiplier_bps: 20_000 }, // 2x
+    ]
+}
+
+fn validate_tiers(tiers: &[TierInfo]) -> Result<()> {
+    require!(
+        !tiers.is_empty() && tiers.len() <= MAX_TIERS,
+        StakingError::InvalidTierConfig
+    );
+    
+    for (i, tier) in tiers.iter().enumerate() {
+        require!(
+            tier.id >= 1 && tier.id as usize <= MAX_TIERS,
+            StakingError::InvalidTierConfig
+        );
+        require!(
+            tier.name.len() <= MAX_TIER_NAME_LEN,
+            StakingError::InvalidTierConfig
+        );
+        require!(
+            tier.min_amount > 0 && tier.min_amount <= tier.max_amount,
+            StakingError::InvalidTierConfig
+        );
+        require!(
+            tier.apy_bps as u64 <= BASIS_POINTS,
+            StakingError::InvalidTierConfig
+        );
+        require!(
+            !tiers[..i].iter().any(|other| other.id == tier.id),
+            StakingError::InvalidTierConfig
+        );
+        
+        // Ranges must be ordered and contiguous so every amount above the minimum maps to a tier
+        if i > 0 {
+            require!(
+                tiers[i - 1].max_amount.checked_add(1) == Some(tier.min_amount),
+                StakingError::InvalidTierConfig
+            );
+        }
+    }
+    
+    // The top tier must be open-ended, otherwise larger stakes would fall back to tier 0
+    require!(
+        tiers.last().map(|tier| tier.max_amount) == Some(u64::MAX),
+        StakingError::InvalidTierConfig
+    );
+    
+    Ok(())
+}
+
+// A tier id that still carries stake must survive a table change, otherwise its index stops
+// advancing and those positions silently stop accruing until re-tiered
+fn validate_staked_tiers_retained(reward_accrual: &RewardAccrual, new_tiers: &[TierInfo]) -> Result<()> {
+    for tier_id in 1..=MAX_TIERS {
+        require!(
+            reward_accrual.tier_weights[tier_id] == 0
+                || new_tiers.iter().any(|tier| tier.id as usize == tier_id),
+            StakingError::StakedTierRemoved
+        );
+    }
+    
+    Ok(())
+}
+
+fn validate_tier_config_update(
+    tiers: &[TierInfo],
+    lock_terms: &[LockTerm],
+    base_apy_bps: u16,
+) -> Result<()> {
+    validate_tiers(tiers)?;
+    validate_lock_terms(lock_terms)?;
+    
+    // Sub-tier stakes (e.g. dust left after a partial unstake) earn at most the lowest tier's APY
+    require!(
+        base_apy_bps <= tiers[0].apy_bps,
+        StakingError::InvalidTierConfig
+    );
+    
+    Ok(())
+}
+
+fn default_penalty_brackets() -> Vec<PenaltyBracket> {
+    vec![
+        PenaltyBracket { min_days: 0, penalty_bps: 200 },  // 2%
+        PenaltyBracket { min_days: 30, penalty_bps: 100 }, // 1%
+        PenaltyBracket { min_days: 90, penalty_bps: 0 },   // No penalty
+    ]
+}
+
+fn validate_penalty_brackets(brackets: &[PenaltyBracket]) -> Result<()> {
+    require!(
+        !brackets.is_empty() && brackets.len() <= MAX_PENALTY_BRACKETS,
+        StakingError::InvalidPenaltySchedule
+    );
+    require!(brackets[0].min_days == 0, StakingError::InvalidPenaltySchedule);
+    
+    for (i, bracket) in brackets.iter().enumerate() {
+        require!(
+            bracket.penalty_bps <= MAX_UNSTAKE_PENALTY_BPS,
+            StakingError::InvalidPenaltySchedule
+        );
+        
+        // Penalties only ever shrink the longer a position has been staked
+        if i > 0 {
+            require!(
+                brackets[i - 1].min_days < bracket.min_days
+                    && brackets[i - 1].penalty_bps >= bracket.penalty_bps,
+                StakingError::InvalidPenaltySchedule
+            );
+        }
+    }
+    
+    Ok(())
+}
+
+// Like anchor-spl's `transfer_checked`, but resolves the mint's transfer hook (if any) against the
+// CPI context's remaining accounts, so hook-enabled Token-2022 mints keep working
+fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+// anchor-spl 0.30 only wraps the deprecated unchecked burn, so build burn_checked directly
+fn burn_checked<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Burn<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let ix = spl_token_2022::instruction::burn_checked(
+        ctx.program.key,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    anchor_lang::solana_program::program::invoke_signed(
+        &ix,
+        &[ctx.accounts.from, ctx.accounts.mint, ctx.accounts.authority],
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+fn validate_penalty_split(split: &PenaltySplit) -> Result<()> {
+    let total = split.escrow_bps as u64 + split.treasury_bps as u64 + split.burn_bps as u64;
+    require!(total == BASIS_POINTS, StakingError::InvalidPenaltySplit);
+    
+    Ok(())
+}
+
+fn validate_council(signers: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !signers.is_empty() && signers.len() <= MAX_COUNCIL_SIGNERS,
+        StakingError::InvalidCouncilConfig
+    );
+    require!(
+        threshold >= 1 && threshold as usize <= signers.len(),
+        StakingError::InvalidCouncilConfig
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require!(
+            !signers[..i].contains(signer),
+            StakingError::InvalidCouncilConfig
+        );
+    }
+    
+    Ok(())
+}
+
+fn validate_lock_terms(lock_terms: &[LockTerm]) -> Result<()> {
+    require!(lock_terms.len() <= MAX_LOCK_TERMS, StakingError::InvalidLockTerm);
+    
+    for (i, term) in lock_terms.iter().enumerate() {
+        require!(term.duration_days > 0, StakingError::InvalidLockTerm);
+        require!(
+            term.multiplier_bps as u64 >= BASIS_POINTS && term.multiplier_bps <= MAX_LOCK_MULTIPLIER_BPS,
+            StakingError::InvalidLockTerm
+        );
+        if i > 0 {
+            require!(
+                lock_terms[i - 1].duration_days < term.duration_days,
+                StakingError::InvalidLockTerm
+            );
+        }
+    }
+    
+    Ok(())
+}
+
+fn find_lock_term(tier_config: &TierConfig, lock_days: u16) -> Result<&LockTerm> {
+    tier_config
+        .lock_terms
+        .iter()
+        .find(|term| term.duration_days == lock_days)
+        .ok_or_else(|| StakingError::InvalidLockTerm.into())
+}
+
+fn find_tier(tier_config: &TierConfig, amount: u64) -> Option<&TierInfo> {
+    tier_config
+        .tiers
+        .iter()
+        .find(|tier| amount >= tier.min_amount && amount <= tier.max_amount)
+}
+
+fn min_stake_amount(tier_config: &TierConfig) -> Result<u64> {
+    tier_config
+        .tiers
+        .first()
+        .map(|tier| tier.min_amount)
+        .ok_or_else(|| StakingError::InvalidTierConfig.into())
+}
+
+fn get_tier(tier_config: &TierConfig, amount: u64) -> u8 {
+    find_tier(tier_config, amount).map(|tier| tier.id).unwrap_or(0) // 0 = no tier
+}
+
+fn update_reward_indices(
+    reward_accrual: &mut RewardAccrual,
+    tier_config: &TierConfig,
+    current_timestamp: i64,
+) -> Result<()> {
+    let time_elapsed = current_timestamp.saturating_sub(reward_accrual.last_index_update);
+    if time_elapsed <= 0 {
+        return Ok(());
+    }
+    
+    // Index growth per tier: apy * scale * time / (year * basis_points^2), scaled by REWARD_INDEX_PRECISION.
+    // Slot 0 accrues the base APY for stakes that have fallen below the lowest tier.
+    // The runway guard's scale only applies to APY; campaign budgets are already reserved.
+    let tier_rates = std::iter::once((0u8, tier_config.base_apy_bps))
+        .chain(tier_config.tiers.iter().map(|tier| (tier.id, tier.apy_bps)))
+        .collect::<Vec<_>>();
+    for (tier_id, apy_bps) in tier_rates {
+        // The truncated remainder is carried into the next update so short windows lose nothing
+        let divisor = SECONDS_PER_YEAR as u128 * BASIS_POINTS as u128 * BASIS_POINTS as u128;
+        let numerator = (apy_bps as u128)
+            .checked_mul(reward_accrual.reward_scale_bps as u128).unwrap()
+            .checked_mul(time_elapsed as u128).unwrap()
+            .checked_mul(REWARD_INDEX_PRECISION).unwrap()
+            .checked_add(reward_accrual.index_remainders[tier_id as usize]).unwrap();
+        let index_delta = numerator / divisor;
+        reward_accrual.index_remainders[tier_id as usize] = numerator % divisor;
+        
+        let index = &mut reward_accrual.reward_indices[tier_id as usize];
+        *index = index.checked_add(index_delta).unwrap();
+        
+        // Everything staked in the tier accrued the same index growth
+        let accrued = reward_accrual.tier_weights[tier_id as usize]
+            .checked_mul(index_delta).unwrap()
+            .checked_div(REWARD_INDEX_PRECISION * BASIS_POINTS as u128).unwrap() as u64;
+        reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.checked_add(accrued).unwrap();
+    }
+    accrue_campaigns(reward_accrual, current_timestamp)?;
+    reward_accrual.last_index_update = current_timestamp;
+    
+    Ok(())
+}
+
+// Emits each campaign's share of the window since the last update into the eligible tiers' indices,
+// so `calculate_rewards` pays it alongside APY, then drops campaigns that ended or spent their budget
+fn accrue_campaigns(reward_accrual: &mut RewardAccrual, current_timestamp: i64) -> Result<()> {
+    let mut campaigns = std::mem::take(&mut reward_accrual.campaigns);
+    for campaign in campaigns.iter_mut() {
+        let window_start = reward_accrual.last_index_update.max(campaign.start_timestamp);
+        let window_end = current_timestamp.min(campaign.end_timestamp);
+        if window_end <= window_start {
+            continue;
+        }
+        
+        // Nothing is emitted (or spent) while no eligible tier has stake
+        let eligible_weight: u128 = (0..=MAX_TIERS)
+            .filter(|&tier_id| campaign.is_eligible(tier_id))
+            .map(|tier_id| reward_accrual.tier_weights[tier_id])
+            .sum();
+        if eligible_weight == 0 {
+            continue;
+        }
+        
+        let remaining_budget = campaign.total_budget.saturating_sub(campaign.distributed);
+        let emitted = (campaign.emission_per_second as u128)
+            .checked_mul((window_end - window_start) as u128).unwrap()
+            .min(remaining_budget as u128) as u64;
+        
+        // Weights are staked_amount * apy_multiplier_bps, matching calculate_rewards' scaling
+        let index_delta = (emitted as u128)
+            .checked_mul(REWARD_INDEX_PRECISION * BASIS_POINTS as u128).unwrap()
+            .checked_div(eligible_weight).unwrap();
+        for tier_id in (0..=MAX_TIERS).filter(|&tier_id| campaign.is_eligible(tier_id)) {
+            let index = &mut reward_accrual.reward_indices[tier_id];
+            *index = index.checked_add(index_delta).unwrap();
+        }
+        
+        campaign.distributed = campaign.distributed.checked_add(emitted).unwrap();
+        reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.checked_add(emitted).unwrap();
     }
+    
+    campaigns.retain(|campaign| {
+        let live = campaign.end_timestamp > current_timestamp && campaign.distributed < campaign.total_budget;
+        if !live {
+            emit!(RewardCampaignEndedEvent {
+                campaign_id: campaign.campaign_id,
+                distributed: campaign.distributed,
+                cancelled: false,
+                timestamp: current_timestamp,
+            });
+        }
+        live
+    });
+    reward_accrual.campaigns = campaigns;
+    
+    Ok(())
 }
 
-fn get_tier_apy(amount: u64) -> Result<u16> {
-    if amount >= INFINITE_MIN {
-        Ok(INFINITE_APY_BPS)
-    } else if amount >= TITANIUM_MIN {
-        Ok(TITANIUM_APY_BPS)
-    } else if amount >= GOLD_MIN {
-        Ok(GOLD_APY_BPS)
+// Sum of weight * apy over every tier; divided by basis_points^2 * year it is the DEFAI accruing
+// per second at full APY
+fn projected_accrual_rate(reward_accrual: &RewardAccrual, tier_config: &TierConfig) -> u128 {
+    std::iter::once((0u8, tier_config.base_apy_bps))
+        .chain(tier_config.tiers.iter().map(|tier| (tier.id, tier.apy_bps)))
+        .map(|(tier_id, apy_bps)| reward_accrual.tier_weights[tier_id as usize].checked_mul(apy_bps as u128).unwrap())
+        .sum()
+}
+
+// Scales APY accrual pro-rata when the escrow, net of committed rewards, cannot fund the current
+// accrual rate for `min_runway_days`, so the remaining funds stretch over that window instead of
+// being drained by whoever claims first. Only accrual after this point is affected.
+fn apply_runway_guard(
+    reward_accrual: &mut RewardAccrual,
+    tier_config: &TierConfig,
+    escrow_balance: u64,
+    current_timestamp: i64,
+) -> Result<()> {
+    let previous_scale_bps = reward_accrual.reward_scale_bps;
+    let committed_rewards = reward_accrual.committed_rewards();
+    let available = escrow_balance.saturating_sub(committed_rewards) as u128;
+    
+    let accrual_rate = projected_accrual_rate(reward_accrual, tier_config);
+    let rate_divisor = SECONDS_PER_YEAR as u128 * BASIS_POINTS as u128 * BASIS_POINTS as u128;
+    let projected_liabilities = accrual_rate
+        .checked_mul(tier_config.min_runway_days as u128 * SECONDS_PER_DAY as u128).unwrap()
+        .checked_div(rate_divisor).unwrap();
+    
+    reward_accrual.reward_scale_bps = if projected_liabilities <= available {
+        BASIS_POINTS as u16
     } else {
-        Err(StakingError::AmountTooLow.into())
+        available
+            .checked_mul(BASIS_POINTS as u128).unwrap()
+            .checked_div(projected_liabilities).unwrap() as u16
+    };
+    
+    if reward_accrual.reward_scale_bps < BASIS_POINTS as u16 || reward_accrual.reward_scale_bps != previous_scale_bps {
+        // Days the available funds last at full APY
+        let runway_days = if accrual_rate == 0 {
+            u64::MAX
+        } else {
+            available
+                .checked_mul(rate_divisor).unwrap()
+                .checked_div(accrual_rate.checked_mul(SECONDS_PER_DAY as u128).unwrap()).unwrap()
+                .min(u64::MAX as u128) as u64
+        };
+        
+        emit!(RunwayWarningEvent {
+            escrow_balance,
+            committed_rewards,
+            projected_liabilities: projected_liabilities.min(u64::MAX as u128) as u64,
+            runway_days,
+            min_runway_days: tier_config.min_runway_days,
+            reward_scale_bps: reward_accrual.reward_scale_bps,
+            previous_scale_bps,
+            timestamp: current_timestamp,
+        });
     }
+    
+    Ok(())
 }
 
 fn calculate_rewards(
     staked_amount: u64,
-    tier_apy_bps: u16,
-    last_claim_timestamp: i64,
-    current_timestamp: i64,
+    reward_index: u128,
+    reward_index_checkpoint: u128,
+    apy_multiplier_bps: u16,
 ) -> Result<u64> {
-    let time_elapsed = (current_timestamp - last_claim_timestamp) as u64;
+    let index_delta = reward_index.saturating_sub(reward_index_checkpoint);
     
-    // Calculate rewards: amount * apy * time / (year * basis_points)
+    // Calculate rewards: amount * (index - checkpoint) * multiplier / (precision * basis_points)
     let rewards = (staked_amount as u128)
-        .checked_mul(tier_apy_bps as u128).unwrap()
-        .checked_mul(time_elapsed as u128).unwrap()
-        .checked_div(SECONDS_PER_YEAR as u128).unwrap()
-        .checked_div(BASIS_POINTS as u128).unwrap() as u64;
+        .checked_mul(index_delta).unwrap()
+        .checked_mul(apy_multiplier_bps as u128).unwrap()
+        .checked_div(REWARD_INDEX_PRECISION * BASIS_POINTS as u128).unwrap() as u64;
     
     Ok(rewards)
 }
 
-fn calculate_unstake_penalty(
-    stake_timestamp: i64,
-    current_timestamp: i64,
-    amount: u64,
+// Credits rewards accrued since the last checkpoint at the stake's current tier,
+// plus any penalty bonus distributed since then.
+// Indices must already be updated to the current timestamp.
+fn accrue_rewards(user_stake: &mut UserStake, reward_accrual: &RewardAccrual) -> Result<u64> {
+    let reward_index = reward_accrual.reward_indices[user_stake.tier as usize];
+    let pending_rewards = calculate_rewards(
+        user_stake.staked_amount,
+        reward_index,
+        user_stake.reward_index_checkpoint,
+        user_stake.apy_multiplier_bps,
+    )?;
+    
+    user_stake.rewards_earned = user_stake.rewards_earned.checked_add(pending_rewards).unwrap();
+    user_stake.reward_index_checkpoint = reward_index;
+    
+    // The bonus is shared by staked amount alone, independent of tier and lock multiplier
+    let pending_bonus = (user_stake.staked_amount as u128)
+        .checked_mul(reward_accrual.bonus_per_share.saturating_sub(user_stake.bonus_checkpoint)).unwrap()
+        .checked_div(REWARD_INDEX_PRECISION).unwrap() as u64;
+    user_stake.bonus_earned = user_stake.bonus_earned.checked_add(pending_bonus).unwrap();
+    user_stake.bonus_checkpoint = reward_accrual.bonus_per_share;
+    
+    accrue_stake_seconds(user_stake, reward_accrual.last_index_update);
+    
+    Ok(pending_rewards)
+}
+
+// Integrates the staked amount over time at the stake's current tier. Reward streams pay out of
+// these totals, so stake changes never need to touch the stream accounts.
+fn accrue_stake_seconds(user_stake: &mut UserStake, current_timestamp: i64) {
+    let time_elapsed = current_timestamp.saturating_sub(user_stake.stake_seconds_updated_at);
+    if time_elapsed <= 0 {
+        return;
+    }
+    
+    let stake_seconds = &mut user_stake.tier_stake_seconds[user_stake.tier as usize];
+    *stake_seconds = stake_seconds
+        .checked_add((user_stake.staked_amount as u128).checked_mul(time_elapsed as u128).unwrap())
+        .unwrap();
+    user_stake.stake_seconds_updated_at = current_timestamp;
+}
+
+// Stream rewards for the stake-seconds accumulated since the checkpoint:
+// sum over tiers of stake_seconds * rate / (year * precision)
+fn calculate_stream_rewards(
+    reward_stream: &RewardStream,
+    tier_stake_seconds: &[u128; MAX_TIERS + 1],
+    checkpoint: &[u128; MAX_TIERS + 1],
 ) -> Result<u64> {
-    let days_staked = (current_timestamp - stake_timestamp) / 86400;
+    let mut rewards: u128 = 0;
+    for tier_id in 0..=MAX_TIERS {
+        let stake_seconds = tier_stake_seconds[tier_id].saturating_sub(checkpoint[tier_id]);
+        let tier_rewards = stake_seconds
+            .checked_mul(reward_stream.tier_rates[tier_id] as u128).unwrap()
+            .checked_div(SECONDS_PER_YEAR as u128 * REWARD_INDEX_PRECISION).unwrap();
+        rewards = rewards.checked_add(tier_rewards).unwrap();
+    }
     
-    let penalty_bps = if days_staked < 30 {
-        200  // 2%
-    } else if days_staked < 90 {
-        100  // 1%
+    Ok(rewards as u64)
+}
+
+// Credits a stream position with the rewards for its stake-seconds since the last settlement.
+// Nothing accrues past the stream's close; if the position's stake-seconds were already settled
+// beyond it (by any stake change or another stream), the window is prorated by time to the close.
+fn accrue_stream_rewards(
+    reward_stream: &RewardStream,
+    user_stake: &mut UserStake,
+    stream_position: &mut StreamPosition,
+    current_timestamp: i64,
+) -> Result<u64> {
+    let settle_until = if reward_stream.closes_at > 0 {
+        current_timestamp.min(reward_stream.closes_at)
     } else {
-        0    // No penalty
+        current_timestamp
     };
+    if settle_until <= stream_position.settled_at {
+        return Ok(0);
+    }
+    
+    accrue_stake_seconds(user_stake, settle_until);
+    let mut pending = calculate_stream_rewards(
+        reward_stream,
+        &user_stake.tier_stake_seconds,
+        &stream_position.stake_seconds_checkpoint,
+    )?;
+    if user_stake.stake_seconds_updated_at > settle_until {
+        let window = user_stake.stake_seconds_updated_at - stream_position.settled_at;
+        pending = (pending as u128)
+            .checked_mul((settle_until - stream_position.settled_at) as u128).unwrap()
+            .checked_div(window as u128).unwrap() as u64;
+    }
+    
+    stream_position.rewards_earned = stream_position.rewards_earned.checked_add(pending).unwrap();
+    stream_position.stake_seconds_checkpoint = user_stake.tier_stake_seconds;
+    stream_position.settled_at = settle_until;
+    
+    Ok(pending)
+}
+
+// Re-derives the tier from the staked amount, moves the position's weight to that tier
+// and checkpoints against that tier's index.
+// Must only be called after `accrue_rewards` so no accrual at the old tier is lost.
+fn set_tier(
+    user_stake: &mut UserStake,
+    tier_config: &TierConfig,
+    reward_accrual: &mut RewardAccrual,
+) -> Result<()> {
+    let old_weight = &mut reward_accrual.tier_weights[user_stake.tier as usize];
+    *old_weight = old_weight.saturating_sub(user_stake.reward_weight);
+    
+    user_stake.tier = get_tier(tier_config, user_stake.staked_amount);
+    user_stake.reward_index_checkpoint = reward_accrual.reward_indices[user_stake.tier as usize];
+    user_stake.bonus_checkpoint = reward_accrual.bonus_per_share;
+    user_stake.reward_weight = (user_stake.staked_amount as u128)
+        .checked_mul(user_stake.apy_multiplier_bps as u128).unwrap();
+    
+    let new_weight = &mut reward_accrual.tier_weights[user_stake.tier as usize];
+    *new_weight = new_weight.checked_add(user_stake.reward_weight).unwrap();
+    
+    Ok(())
+}
+
+// Balance increase of a program vault since `balance_before`. Transfer-fee mints deliver less than
+// the transferred amount, so inbound transfers credit this instead of the nominal amount.
+fn received_amount<'info>(
+    vault: &mut InterfaceAccount<'info, TokenAccount>,
+    balance_before: u64,
+) -> Result<u64> {
+    vault.reload()?;
+    Ok(vault.amount.checked_sub(balance_before).unwrap())
+}
+
+// Accounts an escrow surplus withdrawal touches, shared by the treasurer and council paths
+struct EscrowWithdrawal<'a, 'info> {
+    program_state: &'a Account<'info, ProgramState>,
+    tier_config: &'a Account<'info, TierConfig>,
+    reward_accrual: &'a mut Account<'info, RewardAccrual>,
+    reward_escrow: &'a mut Account<'info, RewardEscrow>,
+    escrow_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    destination_token_account: &'a InterfaceAccount<'info, TokenAccount>,
+    defai_mint: &'a InterfaceAccount<'info, Mint>,
+    token_program: &'a Interface<'info, TokenInterface>,
+}
+
+// Sends `amount` to `destination`; only the surplus above rewards owed to stakers and reserved
+// for campaigns can leave the escrow
+fn withdraw_escrow_surplus<'info>(
+    accounts: EscrowWithdrawal<'_, 'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    destination: Pubkey,
+    authority: Pubkey,
+    timestamp: i64,
+) -> Result<()> {
+    require_keys_eq!(
+        accounts.destination_token_account.key(),
+        destination,
+        StakingError::InvalidWithdrawalDestination
+    );
+    
+    let tier_config = accounts.tier_config;
+    let reward_accrual = accounts.reward_accrual;
+    update_reward_indices(reward_accrual, tier_config, timestamp)?;
+    let surplus = accounts.reward_escrow.total_balance.saturating_sub(reward_accrual.committed_rewards());
+    require!(amount <= surplus, StakingError::WithdrawalExceedsSurplus);
+    
+    let program_state_key = accounts.program_state.key();
+    let escrow_seeds = &[
+        b"reward-escrow",
+        program_state_key.as_ref(),
+        &[accounts.program_state.reward_escrow_bump],
+    ];
+    let escrow_signer = &[&escrow_seeds[..]];
+    
+    let transfer_ctx = CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: accounts.escrow_token_account.to_account_info(),
+            to: accounts.destination_token_account.to_account_info(),
+            authority: accounts.reward_escrow.to_account_info(),
+            mint: accounts.defai_mint.to_account_info(),
+        },
+        escrow_signer,
+    ).with_remaining_accounts(remaining_accounts.to_vec());
+    transfer_checked_with_hook(transfer_ctx, amount, accounts.defai_mint.decimals)?;
+    
+    let reward_escrow = accounts.reward_escrow;
+    reward_escrow.total_balance = reward_escrow.total_balance.checked_sub(amount).unwrap();
+    
+    emit!(EscrowWithdrawnEvent {
+        authority,
+        amount,
+        destination,
+        remaining_balance: reward_escrow.total_balance,
+        accrued_liabilities: reward_accrual.accrued_liabilities,
+    });
+    
+    apply_runway_guard(reward_accrual, tier_config, reward_escrow.total_balance, timestamp)
+}
+
+// Spreads `amount` over every staked token; it stays in the escrow as a liability until claimed
+fn distribute_bonus(reward_accrual: &mut RewardAccrual, amount: u64, total_staked: u64) -> Result<()> {
+    let delta = (amount as u128)
+        .checked_mul(REWARD_INDEX_PRECISION).unwrap()
+        .checked_div(total_staked as u128).unwrap();
+    reward_accrual.bonus_per_share = reward_accrual.bonus_per_share.checked_add(delta).unwrap();
+    reward_accrual.accrued_liabilities = reward_accrual.accrued_liabilities.checked_add(amount).unwrap();
+    
+    Ok(())
+}
+
+// Owner (program authority) or a member holding the given role
+fn is_authorized(
+    program_state: &ProgramState,
+    admin_roles: &AdminRoles,
+    key: &Pubkey,
+    role: u8,
+) -> bool {
+    *key == program_state.authority || admin_roles.has_role(key, role)
+}
+
+fn grant_roles(admin_roles: &mut AdminRoles, member: Pubkey, roles: u8) -> Result<u8> {
+    require!(roles != 0 && roles & !ALL_ROLES == 0, StakingError::InvalidRole);
+    
+    match admin_roles.members.iter_mut().find(|m| m.member == member) {
+        Some(assignment) => {
+            assignment.roles |= roles;
+            Ok(assignment.roles)
+        }
+        None => {
+            require!(
+                admin_roles.members.len() < MAX_ROLE_MEMBERS,
+                StakingError::TooManyRoleMembers
+            );
+            admin_roles.members.push(RoleAssignment { member, roles });
+            Ok(roles)
+        }
+    }
+}
+
+fn revoke_roles(admin_roles: &mut AdminRoles, member: Pubkey, roles: u8) -> Result<u8> {
+    require!(roles != 0 && roles & !ALL_ROLES == 0, StakingError::InvalidRole);
+    
+    let assignment = admin_roles.members
+        .iter_mut()
+        .find(|m| m.member == member)
+        .ok_or(StakingError::InvalidRole)?;
+    assignment.roles &= !roles;
+    let new_roles = assignment.roles;
+    
+    // Drop members left without any role
+    admin_roles.members.retain(|m| m.roles != 0);
+    
+    Ok(new_roles)
+}
+
+fn clear_pending_authority(program_state: &mut ProgramState) -> Result<Pubkey> {
+    let cancelled_authority = program_state.pending_authority
+        .ok_or(StakingError::NoPendingAuthorityChange)?;
+    program_state.pending_authority = None;
+    program_state.authority_change_timestamp = 0;
+    
+    Ok(cancelled_authority)
+}
+
+fn authority_proposal_expired(program_state: &ProgramState, current_timestamp: i64) -> bool {
+    current_timestamp > program_state.authority_change_timestamp.saturating_add(AUTHORITY_PROPOSAL_EXPIRY)
+}
+
+// Updates the pause flags, starting the emergency grace period when unstaking gets paused.
+// Briefly unpausing does not reset the clock: a re-pause only restarts the grace period once
+// unstaking has been open for MIN_UNSTAKE_RESUME_WINDOW, so users had a real chance to exit.
+fn set_pause_flags(program_state: &mut ProgramState, pause_flags: u8, current_timestamp: i64) {
+    let unstake_was_paused = program_state.is_paused(PAUSE_UNSTAKE);
+    program_state.pause_flags = pause_flags;
+    
+    if !program_state.is_paused(PAUSE_UNSTAKE) {
+        if unstake_was_paused {
+            program_state.unstake_resumed_at = current_timestamp;
+        }
+    } else if !unstake_was_paused && unstake_resume_window_elapsed(program_state, current_timestamp) {
+        program_state.paused_at = current_timestamp;
+    }
+}
+
+fn unstake_resume_window_elapsed(program_state: &ProgramState, current_timestamp: i64) -> bool {
+    current_timestamp >= program_state.unstake_resumed_at.saturating_add(MIN_UNSTAKE_RESUME_WINDOW)
+}
+
+fn emergency_exit_open(program_state: &ProgramState, current_timestamp: i64) -> bool {
+    program_state.is_paused(PAUSE_UNSTAKE)
+        && current_timestamp >= program_state.paused_at.saturating_add(program_state.emergency_grace_period)
+}
+
+fn set_aggregate_tier(position_counter: &mut PositionCounter, tier_config: &TierConfig) {
+    position_counter.aggregate_tier = get_tier(tier_config, position_counter.total_staked);
+}
+
+fn unstake_penalty_bps(
+    penalty_config: &PenaltyConfig,
+    stake_timestamp: i64,
+    current_timestamp: i64,
+) -> u16 {
+    let brackets = &penalty_config.brackets;
+    let elapsed = current_timestamp.saturating_sub(stake_timestamp).max(0);
+    
+    // Last bracket whose start has been reached; the first always starts at day 0
+    let i = brackets
+        .iter()
+        .rposition(|b| elapsed >= b.min_days as i64 * SECONDS_PER_DAY)
+        .unwrap_or(0);
+    let bracket = &brackets[i];
     
+    match brackets.get(i + 1) {
+        // Slide from this bracket's rate toward the next one, by the second
+        Some(next) if penalty_config.linear_decay => {
+            let start = bracket.min_days as i64 * SECONDS_PER_DAY;
+            let span = (next.min_days - bracket.min_days) as i64 * SECONDS_PER_DAY;
+            let drop = (bracket.penalty_bps - next.penalty_bps) as i64;
+            bracket.penalty_bps - (drop * (elapsed - start) / span) as u16
+        }
+        _ => bracket.penalty_bps,
+    }
+}
+
+fn calculate_unstake_penalty(amount: u64, penalty_bps: u16) -> Result<u64> {
     Ok((amount as u128 * penalty_bps as u128 / BASIS_POINTS as u128) as u64)
 }
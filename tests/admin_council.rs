@@ -0,0 +1,137 @@
+mod common;
+
+use common::{assert_staking_error, TestPool};
+use defai_staking::{
+    AdminAction, AdminCouncil, AdminProposal, ProgramState, StakingError, ADMIN_TIMELOCK_DURATION, PAUSE_STAKE,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+async fn create_signer(pool: &mut TestPool) -> Keypair {
+    pool.create_user(0).await.0
+}
+
+// 2-of-3 council that has taken over the program authority
+async fn council_pool() -> (TestPool, [Keypair; 3]) {
+    let mut pool = TestPool::new().await;
+    let signers = [create_signer(&mut pool).await, create_signer(&mut pool).await, create_signer(&mut pool).await];
+    pool.initialize_admin_council(signers.iter().map(|signer| signer.pubkey()).collect(), 2).await.unwrap();
+    pool.propose_authority_change(pool.admin_council()).await.unwrap();
+
+    // One approval is not enough; the second starts the timelock
+    let accept = pool.create_admin_proposal(&signers[0], AdminAction::AcceptAuthority).await.unwrap();
+    assert_staking_error(
+        pool.execute_admin_proposal(&signers[0], accept).await,
+        StakingError::ProposalThresholdNotReached,
+    );
+    assert_staking_error(
+        pool.approve_admin_proposal(&signers[0], accept).await,
+        StakingError::ProposalAlreadyApproved,
+    );
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    pool.approve_admin_proposal(&signers[1], accept).await.unwrap();
+    let proposal: AdminProposal = pool.account(pool.admin_proposal(accept)).await;
+    assert_eq!(proposal.approvals.len(), 2);
+    assert!(proposal.threshold_reached_at > 0);
+
+    // The timelock runs from the threshold, not from creation
+    pool.warp(ADMIN_TIMELOCK_DURATION - 60).await;
+    assert_staking_error(
+        pool.execute_admin_proposal(&signers[1], accept).await,
+        StakingError::TimelockNotExpired,
+    );
+    pool.warp(60).await;
+    pool.execute_admin_proposal(&signers[2], accept).await.unwrap();
+    assert_staking_error(
+        pool.execute_admin_proposal(&signers[2], accept).await,
+        StakingError::ProposalAlreadyExecuted,
+    );
+
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.authority, pool.admin_council());
+    (pool, signers)
+}
+
+#[tokio::test]
+async fn council_actions_need_the_threshold_and_the_timelock() {
+    let (mut pool, signers) = council_pool().await;
+
+    let outsider = create_signer(&mut pool).await;
+    let pause = AdminAction::SetPauseFlags { pause_flags: PAUSE_STAKE };
+    assert_staking_error(
+        pool.create_admin_proposal(&outsider, pause.clone()).await.map(|_| ()),
+        StakingError::NotCouncilSigner,
+    );
+
+    let proposal_id = pool.create_admin_proposal(&signers[2], pause).await.unwrap();
+    assert_staking_error(
+        pool.approve_admin_proposal(&outsider, proposal_id).await,
+        StakingError::NotCouncilSigner,
+    );
+    pool.approve_admin_proposal(&signers[0], proposal_id).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    pool.execute_admin_proposal(&signers[1], proposal_id).await.unwrap();
+
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.pause_flags, PAUSE_STAKE);
+}
+
+#[tokio::test]
+async fn changing_the_council_invalidates_open_proposals() {
+    let (mut pool, signers) = council_pool().await;
+
+    // Already past the threshold when the council changes
+    let pause = pool
+        .create_admin_proposal(&signers[0], AdminAction::SetPauseFlags { pause_flags: PAUSE_STAKE })
+        .await
+        .unwrap();
+    pool.approve_admin_proposal(&signers[1], pause).await.unwrap();
+
+    // Replace the third signer and raise the threshold to 3-of-3
+    let replacement = create_signer(&mut pool).await;
+    let update = pool
+        .create_admin_proposal(
+            &signers[0],
+            AdminAction::UpdateCouncil {
+                signers: vec![signers[0].pubkey(), signers[1].pubkey(), replacement.pubkey()],
+                threshold: 3,
+            },
+        )
+        .await
+        .unwrap();
+    pool.approve_admin_proposal(&signers[2], update).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    pool.execute_admin_proposal(&signers[0], update).await.unwrap();
+
+    let council: AdminCouncil = pool.account(pool.admin_council()).await;
+    assert_eq!(council.epoch, 1);
+    assert_eq!(council.threshold, 3);
+    assert_staking_error(pool.execute_admin_proposal(&signers[0], pause).await, StakingError::StaleAdminProposal);
+    assert_staking_error(
+        pool.approve_admin_proposal(&replacement, pause).await,
+        StakingError::StaleAdminProposal,
+    );
+    assert_staking_error(
+        pool.create_admin_proposal(&signers[2], AdminAction::SetPauseFlags { pause_flags: PAUSE_STAKE })
+            .await
+            .map(|_| ()),
+        StakingError::NotCouncilSigner,
+    );
+
+    // The new council needs all three approvals
+    let pause = pool
+        .create_admin_proposal(&replacement, AdminAction::SetPauseFlags { pause_flags: PAUSE_STAKE })
+        .await
+        .unwrap();
+    pool.approve_admin_proposal(&signers[0], pause).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    assert_staking_error(
+        pool.execute_admin_proposal(&signers[0], pause).await,
+        StakingError::ProposalThresholdNotReached,
+    );
+    pool.approve_admin_proposal(&signers[1], pause).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    pool.execute_admin_proposal(&replacement, pause).await.unwrap();
+
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.pause_flags, PAUSE_STAKE);
+}
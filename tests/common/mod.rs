@@ -0,0 +1,1207 @@
+// Shared program-test harness. Each integration test builds on a freshly initialized pool.
+#![allow(dead_code)]
+
+pub mod transfer_hook;
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use defai_staking::{
+    GlobalConfig, StakingError, ADMIN_TIMELOCK_DURATION, BASIS_POINTS, REWARD_INDEX_PRECISION, SECONDS_PER_DAY,
+    SECONDS_PER_YEAR,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token_2022::extension::{transfer_fee, BaseStateWithExtensions, ExtensionType, StateWithExtensions};
+use spl_transfer_hook_interface::get_extra_account_metas_address;
+use transfer_hook::HOOK_PROGRAM_ID;
+
+pub const DECIMALS: u8 = 6;
+pub const ESCROW_FUNDING: u64 = 1_000_000_000 * 10u64.pow(DECIMALS as u32);
+
+// Anchor's entrypoint wants the account slice to live as long as the accounts themselves, which
+// the program-test builtin shim doesn't promise; leaking a copy is fine for the length of a test.
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    defai_staking::entry(program_id, accounts, data)
+}
+
+pub fn assert_staking_error(result: Result<(), BanksClientError>, expected: StakingError) {
+    let code = u32::from(expected);
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => assert_eq!(actual, code),
+        other => panic!("expected custom error {code}, got {other:?}"),
+    }
+}
+
+// Index growth over `seconds` of a tier at `apy_bps` and full reward scale, and the remainder
+// carried into the next update
+pub fn index_growth(apy_bps: u16, seconds: i64, remainder: u128) -> (u128, u128) {
+    scaled_index_growth(apy_bps, BASIS_POINTS as u16, seconds, remainder)
+}
+
+// Same, with the runway guard scaling APY down to `scale_bps`
+pub fn scaled_index_growth(apy_bps: u16, scale_bps: u16, seconds: i64, remainder: u128) -> (u128, u128) {
+    let divisor = SECONDS_PER_YEAR as u128 * BASIS_POINTS as u128 * BASIS_POINTS as u128;
+    let numerator = apy_bps as u128 * scale_bps as u128 * seconds as u128 * REWARD_INDEX_PRECISION + remainder;
+    (numerator / divisor, numerator % divisor)
+}
+
+// Token-2022 extensions configured on the pool's staking mint
+#[derive(Clone, Copy)]
+pub enum MintKind {
+    Plain,
+    TransferFee { basis_points: u16, maximum_fee: u64 },
+    TransferHook,
+}
+
+pub struct TestPool {
+    pub context: ProgramTestContext,
+    pub admin: Keypair,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub global_config: Pubkey,
+    pub program_state: Pubkey,
+    pub stake_vault: Pubkey,
+    pub reward_escrow: Pubkey,
+    pub escrow_vault: Pubkey,
+    pub tier_config: Pubkey,
+    pub reward_accrual: Pubkey,
+    pub penalty_config: Pubkey,
+    pub admin_roles: Pubkey,
+    pub admin_tokens: Pubkey,
+    pub hook_counter: Option<Pubkey>,
+}
+
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &defai_staking::ID).0
+}
+
+impl TestPool {
+    // Initializes a pool with escrow, tier and penalty configs for a plain Token-2022 mint
+    pub async fn new() -> Self {
+        Self::with_mint(MintKind::Plain).await
+    }
+
+    pub async fn with_mint(mint_kind: MintKind) -> Self {
+        let mut program_test = ProgramTest::new("defai_staking", defai_staking::ID, processor!(process_instruction));
+        let admin = Keypair::new();
+        let mint = Keypair::new();
+
+        program_test.add_account(
+            admin.pubkey(),
+            Account::new(1_000 * 1_000_000_000, 0, &system_program::ID),
+        );
+
+        // The global config is created by the upgrade authority, which a builtin program doesn't have
+        let (global_config, bump) = Pubkey::find_program_address(&[b"global-config"], &defai_staking::ID);
+        let mut data = Vec::new();
+        GlobalConfig { admin: admin.pubkey(), bump }.try_serialize(&mut data).unwrap();
+        program_test.add_account(
+            global_config,
+            Account { lamports: 1_000_000_000, data, owner: defai_staking::ID, executable: false, rent_epoch: 0 },
+        );
+
+        // The hook's accounts are derived from the mint, so they can exist before the mint does
+        let hook_counter = matches!(mint_kind, MintKind::TransferHook).then(|| {
+            let counter = Pubkey::new_unique();
+            program_test.add_program(
+                "mock_transfer_hook",
+                HOOK_PROGRAM_ID,
+                processor!(transfer_hook::process_instruction),
+            );
+            program_test.add_account(
+                get_extra_account_metas_address(&mint.pubkey(), &HOOK_PROGRAM_ID),
+                transfer_hook::validation_account(&counter),
+            );
+            program_test.add_account(counter, transfer_hook::counter_account());
+            counter
+        });
+
+        let context = program_test.start_with_context().await;
+        let token_program = spl_token_2022::ID;
+        let program_state = pda(&[b"program-state", mint.pubkey().as_ref()]);
+        let mut pool = Self {
+            context,
+            admin,
+            mint: mint.pubkey(),
+            token_program,
+            global_config,
+            program_state,
+            stake_vault: pda(&[b"stake-vault", program_state.as_ref()]),
+            reward_escrow: pda(&[b"reward-escrow", program_state.as_ref()]),
+            escrow_vault: pda(&[b"escrow-vault", program_state.as_ref()]),
+            tier_config: pda(&[b"tier-config", program_state.as_ref()]),
+            reward_accrual: pda(&[b"reward-accrual", program_state.as_ref()]),
+            penalty_config: pda(&[b"penalty-config", program_state.as_ref()]),
+            admin_roles: pda(&[b"admin-roles", program_state.as_ref()]),
+            admin_tokens: Pubkey::default(),
+            hook_counter,
+        };
+
+        pool.create_mint(&mint, mint_kind).await;
+        pool.initialize().await;
+        pool.admin_tokens = pool.create_token_account(&pool.admin.pubkey()).await;
+
+        pool
+    }
+
+    async fn create_mint(&mut self, mint: &Keypair, mint_kind: MintKind) {
+        let admin = self.admin.pubkey();
+        let (extensions, extension_instructions) = match mint_kind {
+            MintKind::Plain => (vec![], vec![]),
+            MintKind::TransferFee { basis_points, maximum_fee } => (
+                vec![ExtensionType::TransferFeeConfig],
+                vec![transfer_fee::instruction::initialize_transfer_fee_config(
+                    &self.token_program,
+                    &mint.pubkey(),
+                    Some(&admin),
+                    Some(&admin),
+                    basis_points,
+                    maximum_fee,
+                )
+                .unwrap()],
+            ),
+            MintKind::TransferHook => (
+                vec![ExtensionType::TransferHook],
+                vec![spl_token_2022::extension::transfer_hook::instruction::initialize(
+                    &self.token_program,
+                    &mint.pubkey(),
+                    Some(admin),
+                    Some(HOOK_PROGRAM_ID),
+                )
+                .unwrap()],
+            ),
+        };
+
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions).unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let mut instructions = vec![system_instruction::create_account(
+            &self.context.payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(space),
+            space as u64,
+            &self.token_program,
+        )];
+        instructions.extend(extension_instructions);
+        instructions.push(
+            spl_token_2022::instruction::initialize_mint2(&self.token_program, &mint.pubkey(), &admin, None, DECIMALS)
+                .unwrap(),
+        );
+        self.send(&instructions, &[mint]).await.unwrap();
+    }
+
+    // A plain mint, minted by the pool admin, for paying out reward streams
+    pub async fn create_reward_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        self.create_mint(&mint, MintKind::Plain).await;
+        mint.pubkey()
+    }
+
+    async fn initialize(&mut self) {
+        let admin = self.admin.pubkey();
+        let instructions = [
+            Instruction {
+                program_id: defai_staking::ID,
+                accounts: defai_staking::accounts::InitializeProgram {
+                    global_config: self.global_config,
+                    program_state: self.program_state,
+                    stake_vault: self.stake_vault,
+                    admin_roles: self.admin_roles,
+                    authority: admin,
+                    defai_mint: self.mint,
+                    token_program: self.token_program,
+                    system_program: system_program::ID,
+                    rent: sysvar::rent::ID,
+                }
+                .to_account_metas(None),
+                data: defai_staking::instruction::InitializeProgram { defai_mint: self.mint }.data(),
+            },
+            Instruction {
+                program_id: defai_staking::ID,
+                accounts: defai_staking::accounts::InitializeEscrow {
+                    program_state: self.program_state,
+                    reward_escrow: self.reward_escrow,
+                    escrow_token_account: self.escrow_vault,
+                    authority: admin,
+                    defai_mint: self.mint,
+                    token_program: self.token_program,
+                    system_program: system_program::ID,
+                    rent: sysvar::rent::ID,
+                }
+                .to_account_metas(None),
+                data: defai_staking::instruction::InitializeEscrow {}.data(),
+            },
+            Instruction {
+                program_id: defai_staking::ID,
+                accounts: defai_staking::accounts::InitializeTierConfig {
+                    program_state: self.program_state,
+                    tier_config: self.tier_config,
+                    reward_accrual: self.reward_accrual,
+                    authority: admin,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: defai_staking::instruction::InitializeTierConfig {}.data(),
+            },
+            Instruction {
+                program_id: defai_staking::ID,
+                accounts: defai_staking::accounts::InitializePenaltyConfig {
+                    program_state: self.program_state,
+                    penalty_config: self.penalty_config,
+                    authority: admin,
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: defai_staking::instruction::InitializePenaltyConfig {}.data(),
+            },
+        ];
+        let admin = self.admin.insecure_clone();
+        self.send(&instructions, &[&admin]).await.unwrap();
+    }
+
+    pub async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        // A fresh blockhash keeps otherwise identical transactions from being deduplicated
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp
+    }
+
+    pub async fn warp(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn warp_days(&mut self, days: i64) {
+        self.warp(days * SECONDS_PER_DAY).await;
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(address).await.unwrap().unwrap();
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        self.create_token_account_for_mint(self.mint, owner).await
+    }
+
+    pub async fn create_token_account_for_mint(&mut self, mint: Pubkey, owner: &Pubkey) -> Pubkey {
+        let mint_account = self.context.banks_client.get_account(mint).await.unwrap().unwrap();
+        let mint_extensions = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_account.data)
+            .unwrap()
+            .get_extension_types()
+            .unwrap();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(
+            &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+        )
+        .unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+
+        let token_account = Keypair::new();
+        let instructions = [
+            system_instruction::create_account(
+                &self.context.payer.pubkey(),
+                &token_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &self.token_program,
+            ),
+            spl_token_2022::instruction::initialize_account3(
+                &self.token_program,
+                &token_account.pubkey(),
+                &mint,
+                owner,
+            )
+            .unwrap(),
+        ];
+        self.send(&instructions, &[&token_account]).await.unwrap();
+
+        token_account.pubkey()
+    }
+
+    pub async fn mint_to(&mut self, token_account: Pubkey, amount: u64) {
+        self.mint_tokens(self.mint, token_account, amount).await;
+    }
+
+    pub async fn mint_tokens(&mut self, mint: Pubkey, token_account: Pubkey, amount: u64) {
+        let instruction = spl_token_2022::instruction::mint_to(
+            &self.token_program,
+            &mint,
+            &token_account,
+            &self.admin.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        let admin = self.admin.insecure_clone();
+        self.send(&[instruction], &[&admin]).await.unwrap();
+    }
+
+    // A funded wallet holding `amount` staking tokens
+    pub async fn create_user(&mut self, amount: u64) -> (Keypair, Pubkey) {
+        let user = Keypair::new();
+        let transfer = system_instruction::transfer(&self.context.payer.pubkey(), &user.pubkey(), 1_000_000_000);
+        self.send(&[transfer], &[]).await.unwrap();
+
+        let token_account = self.create_token_account(&user.pubkey()).await;
+        self.mint_to(token_account, amount).await;
+
+        (user, token_account)
+    }
+
+    // Extra accounts every token transfer needs, forwarded as remaining accounts
+    pub fn transfer_accounts(&self) -> Vec<AccountMeta> {
+        match self.hook_counter {
+            Some(counter) => vec![
+                AccountMeta::new_readonly(HOOK_PROGRAM_ID, false),
+                AccountMeta::new_readonly(get_extra_account_metas_address(&self.mint, &HOOK_PROGRAM_ID), false),
+                AccountMeta::new(counter, false),
+            ],
+            None => Vec::new(),
+        }
+    }
+
+    // (Execute calls, total amount) seen by the mock transfer hook
+    pub async fn hook_calls(&mut self) -> (u64, u64) {
+        let counter = self.hook_counter.expect("pool mint has no transfer hook");
+        let account = self.context.banks_client.get_account(counter).await.unwrap().unwrap();
+        transfer_hook::read_counter(&account.data)
+    }
+
+    fn instruction(&self, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+        let mut accounts = accounts.to_account_metas(None);
+        accounts.extend(self.transfer_accounts());
+        Instruction { program_id: defai_staking::ID, accounts, data: data.data() }
+    }
+
+    pub fn position_counter(&self, user: &Pubkey) -> Pubkey {
+        pda(&[b"position-counter", self.program_state.as_ref(), user.as_ref()])
+    }
+
+    pub fn user_stake(&self, user: &Pubkey, position_id: u64) -> Pubkey {
+        pda(&[b"user-stake", self.program_state.as_ref(), user.as_ref(), &position_id.to_le_bytes()])
+    }
+
+    pub async fn fund_escrow(&mut self, amount: u64) -> Result<(), BanksClientError> {
+        self.mint_to(self.admin_tokens, amount).await;
+
+        let admin = self.admin.pubkey();
+        let instruction = self.instruction(
+            defai_staking::accounts::FundEscrow {
+                program_state: self.program_state,
+                reward_escrow: self.reward_escrow,
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                escrow_token_account: self.escrow_vault,
+                funder_token_account: self.admin_tokens,
+                funder_record: pda(&[b"funder", self.program_state.as_ref(), admin.as_ref()]),
+                funder: admin,
+                defai_mint: self.mint,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            defai_staking::instruction::FundEscrow { amount },
+        );
+        let admin = self.admin.insecure_clone();
+        self.send(&[instruction], &[&admin]).await
+    }
+
+    // Stakes into a new position and returns its id
+    pub async fn stake(
+        &mut self,
+        user: &Keypair,
+        user_tokens: Pubkey,
+        amount: u64,
+        lock_days: u16,
+    ) -> Result<u64, BanksClientError> {
+        let position_counter = self.position_counter(&user.pubkey());
+        let position_id = match self.context.banks_client.get_account(position_counter).await.unwrap() {
+            Some(account) => {
+                defai_staking::PositionCounter::try_deserialize(&mut account.data.as_slice())
+                    .unwrap()
+                    .next_position_id
+            }
+            None => 0,
+        };
+
+        let instruction = self.instruction(
+            defai_staking::accounts::StakeTokens {
+                program_state: self.program_state,
+                position_counter,
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+                stake_vault: self.stake_vault,
+                user_token_account: user_tokens,
+                defai_mint: self.mint,
+                user: user.pubkey(),
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            defai_staking::instruction::StakeTokens { amount, lock_days },
+        );
+        self.send(&[instruction], &[user]).await?;
+
+        Ok(position_id)
+    }
+
+    pub async fn unstake(
+        &mut self,
+        user: &Keypair,
+        user_tokens: Pubkey,
+        position_id: u64,
+        amount: u64,
+        treasury: Option<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::UnstakeTokens {
+                program_state: self.program_state,
+                position_counter: self.position_counter(&user.pubkey()),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                penalty_config: self.penalty_config,
+                stake_vault: self.stake_vault,
+                user_token_account: user_tokens,
+                reward_escrow: self.reward_escrow,
+                escrow_token_account: self.escrow_vault,
+                treasury_token_account: treasury,
+                defai_mint: self.mint,
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::UnstakeTokens { position_id, amount },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub fn unbonding_ticket(&self, user: &Pubkey, ticket_id: u64) -> Pubkey {
+        pda(&[b"unbonding-ticket", self.program_state.as_ref(), user.as_ref(), &ticket_id.to_le_bytes()])
+    }
+
+    // Moves an amount into a new unbonding ticket and returns its id
+    pub async fn request_unstake(
+        &mut self,
+        user: &Keypair,
+        position_id: u64,
+        amount: u64,
+    ) -> Result<u64, BanksClientError> {
+        let position_counter = self.position_counter(&user.pubkey());
+        let counter: defai_staking::PositionCounter = self.account(position_counter).await;
+        let ticket_id = counter.next_ticket_id;
+
+        let instruction = self.instruction(
+            defai_staking::accounts::RequestUnstake {
+                program_state: self.program_state,
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+                position_counter,
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                unbonding_ticket: self.unbonding_ticket(&user.pubkey(), ticket_id),
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                system_program: system_program::ID,
+            },
+            defai_staking::instruction::RequestUnstake { position_id, amount },
+        );
+        self.send(&[instruction], &[user]).await?;
+
+        Ok(ticket_id)
+    }
+
+    pub async fn complete_unstake(
+        &mut self,
+        user: &Keypair,
+        user_tokens: Pubkey,
+        position_id: u64,
+        ticket_id: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::CompleteUnstake {
+                program_state: self.program_state,
+                unbonding_ticket: self.unbonding_ticket(&user.pubkey(), ticket_id),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                stake_vault: self.stake_vault,
+                user_token_account: user_tokens,
+                defai_mint: self.mint,
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::CompleteUnstake { ticket_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn cancel_unstake(&mut self, user: &Keypair, position_id: u64, ticket_id: u64) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::CancelUnstake {
+                program_state: self.program_state,
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+                position_counter: self.position_counter(&user.pubkey()),
+                unbonding_ticket: self.unbonding_ticket(&user.pubkey(), ticket_id),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                user: user.pubkey(),
+                owner: user.pubkey(),
+            },
+            defai_staking::instruction::CancelUnstake { ticket_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn claim(&mut self, user: &Keypair, user_tokens: Pubkey, position_id: u64) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::ClaimRewards {
+                program_state: self.program_state,
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+                escrow_token_account: self.escrow_vault,
+                user_token_account: user_tokens,
+                defai_mint: self.mint,
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::ClaimRewards { position_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn compound(&mut self, user: &Keypair, position_id: u64) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::CompoundRewards {
+                program_state: self.program_state,
+                position_counter: self.position_counter(&user.pubkey()),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+                escrow_token_account: self.escrow_vault,
+                stake_vault: self.stake_vault,
+                defai_mint: self.mint,
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::CompoundRewards { position_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn close_stake(&mut self, user: &Keypair, user_tokens: Pubkey, position_id: u64) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::CloseStake {
+                program_state: self.program_state,
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                position_counter: self.position_counter(&user.pubkey()),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                reward_escrow: self.reward_escrow,
+                escrow_token_account: self.escrow_vault,
+                user_token_account: user_tokens,
+                defai_mint: self.mint,
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::CloseStake { position_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn create_reward_campaign(
+        &mut self,
+        start_timestamp: i64,
+        end_timestamp: i64,
+        eligible_tiers: u16,
+        emission_per_second: u64,
+        total_budget: u64,
+    ) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::ManageRewardCampaign {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            tier_config: self.tier_config,
+            reward_accrual: self.reward_accrual,
+            reward_escrow: self.reward_escrow,
+            authority: self.admin.pubkey(),
+        };
+        self.send_as_admin(
+            accounts,
+            defai_staking::instruction::CreateRewardCampaign {
+                start_timestamp,
+                end_timestamp,
+                eligible_tiers,
+                emission_per_second,
+                total_budget,
+            },
+        )
+        .await
+    }
+
+    pub fn reward_stream(&self, stream_id: u64) -> Pubkey {
+        pda(&[b"reward-stream", self.program_state.as_ref(), &stream_id.to_le_bytes()])
+    }
+
+    pub fn stream_vault(&self, stream_id: u64) -> Pubkey {
+        pda(&[b"stream-vault", self.reward_stream(stream_id).as_ref()])
+    }
+
+    pub fn stream_position(&self, stream_id: u64, user: &Pubkey, position_id: u64) -> Pubkey {
+        pda(&[
+            b"stream-position",
+            self.reward_stream(stream_id).as_ref(),
+            self.user_stake(user, position_id).as_ref(),
+        ])
+    }
+
+    async fn stream_mint(&mut self, stream_id: u64) -> Pubkey {
+        let reward_stream: defai_staking::RewardStream = self.account(self.reward_stream(stream_id)).await;
+        reward_stream.reward_mint
+    }
+
+    // Creates a stream paying out `reward_mint` and returns its id
+    pub async fn create_reward_stream(
+        &mut self,
+        reward_mint: Pubkey,
+        tier_rates: Vec<u64>,
+    ) -> Result<u64, BanksClientError> {
+        let program_state: defai_staking::ProgramState = self.account(self.program_state).await;
+        let stream_id = program_state.next_stream_id;
+
+        let accounts = defai_staking::accounts::CreateRewardStream {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            reward_stream: self.reward_stream(stream_id),
+            stream_vault: self.stream_vault(stream_id),
+            reward_mint,
+            authority: self.admin.pubkey(),
+            token_program: self.token_program,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::CreateRewardStream { tier_rates }).await?;
+
+        Ok(stream_id)
+    }
+
+    // Mints `amount` of the stream's reward token to the admin and funds the stream with it
+    pub async fn fund_reward_stream(&mut self, stream_id: u64, amount: u64) -> Result<(), BanksClientError> {
+        let reward_mint = self.stream_mint(stream_id).await;
+        let funder_tokens = self.create_token_account_for_mint(reward_mint, &self.admin.pubkey()).await;
+        self.mint_tokens(reward_mint, funder_tokens, amount).await;
+
+        let accounts = defai_staking::accounts::FundRewardStream {
+            program_state: self.program_state,
+            reward_stream: self.reward_stream(stream_id),
+            stream_vault: self.stream_vault(stream_id),
+            funder_token_account: funder_tokens,
+            funder: self.admin.pubkey(),
+            reward_mint,
+            token_program: self.token_program,
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::FundRewardStream { stream_id, amount }).await
+    }
+
+    pub async fn join_reward_stream(
+        &mut self,
+        user: &Keypair,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::JoinRewardStream {
+                program_state: self.program_state,
+                reward_stream: self.reward_stream(stream_id),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                stream_position: self.stream_position(stream_id, &user.pubkey(), position_id),
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                system_program: system_program::ID,
+            },
+            defai_staking::instruction::JoinRewardStream { stream_id, position_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn claim_reward_stream(
+        &mut self,
+        user: &Keypair,
+        user_reward_tokens: Pubkey,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<(), BanksClientError> {
+        let reward_mint = self.stream_mint(stream_id).await;
+        let instruction = self.instruction(
+            defai_staking::accounts::ClaimRewardStream {
+                program_state: self.program_state,
+                reward_stream: self.reward_stream(stream_id),
+                stream_vault: self.stream_vault(stream_id),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                stream_position: self.stream_position(stream_id, &user.pubkey(), position_id),
+                user_token_account: user_reward_tokens,
+                reward_mint,
+                user: user.pubkey(),
+                owner: user.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::ClaimRewardStream { stream_id, position_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    pub async fn leave_reward_stream(
+        &mut self,
+        user: &Keypair,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::LeaveRewardStream {
+                program_state: self.program_state,
+                reward_stream: self.reward_stream(stream_id),
+                user_stake: self.user_stake(&user.pubkey(), position_id),
+                stream_position: self.stream_position(stream_id, &user.pubkey(), position_id),
+                user: user.pubkey(),
+                owner: user.pubkey(),
+            },
+            defai_staking::instruction::LeaveRewardStream { stream_id, position_id },
+        );
+        self.send(&[instruction], &[user]).await
+    }
+
+    // Settles `owner`'s stream position, cranked by the test payer
+    pub async fn settle_reward_stream(
+        &mut self,
+        owner: &Pubkey,
+        owner_reward_tokens: Pubkey,
+        stream_id: u64,
+        position_id: u64,
+    ) -> Result<(), BanksClientError> {
+        let reward_mint = self.stream_mint(stream_id).await;
+        let instruction = self.instruction(
+            defai_staking::accounts::SettleRewardStream {
+                program_state: self.program_state,
+                reward_stream: self.reward_stream(stream_id),
+                stream_vault: self.stream_vault(stream_id),
+                user_stake: self.user_stake(owner, position_id),
+                stream_position: self.stream_position(stream_id, owner, position_id),
+                owner_token_account: owner_reward_tokens,
+                reward_mint,
+                owner: *owner,
+                caller: self.context.payer.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::SettleRewardStream { stream_id, position_id },
+        );
+        self.send(&[instruction], &[]).await
+    }
+
+    pub async fn propose_reward_stream_close(&mut self, stream_id: u64) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::ManageRewardStream {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            reward_stream: self.reward_stream(stream_id),
+            authority: self.admin.pubkey(),
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::ProposeRewardStreamClose { stream_id }).await
+    }
+
+    pub async fn close_reward_stream(&mut self, stream_id: u64, destination: Pubkey) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::CloseRewardStream {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            reward_stream: self.reward_stream(stream_id),
+            stream_vault: self.stream_vault(stream_id),
+            destination_token_account: destination,
+            reward_mint: self.stream_mint(stream_id).await,
+            authority: self.admin.pubkey(),
+            token_program: self.token_program,
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::CloseRewardStream { stream_id }).await
+    }
+
+    pub async fn pause(&mut self, pause_flags: u8) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::PauseProgram {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            authority: self.admin.pubkey(),
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::PauseProgram { pause_flags }).await
+    }
+
+    pub fn admin_council(&self) -> Pubkey {
+        pda(&[b"admin-council", self.program_state.as_ref()])
+    }
+
+    pub fn admin_proposal(&self, proposal_id: u64) -> Pubkey {
+        pda(&[b"admin-proposal", self.admin_council().as_ref(), &proposal_id.to_le_bytes()])
+    }
+
+    pub async fn initialize_admin_council(&mut self, signers: Vec<Pubkey>, threshold: u8) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::InitializeAdminCouncil {
+            program_state: self.program_state,
+            admin_council: self.admin_council(),
+            authority: self.admin.pubkey(),
+            system_program: system_program::ID,
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::InitializeAdminCouncil { signers, threshold }).await
+    }
+
+    pub async fn propose_authority_change(&mut self, new_authority: Pubkey) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::UpdateAuthority {
+            program_state: self.program_state,
+            authority: self.admin.pubkey(),
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::ProposeAuthorityChange { new_authority }).await
+    }
+
+    // Creates a council proposal, approved by its proposer, and returns its id
+    pub async fn create_admin_proposal(
+        &mut self,
+        proposer: &Keypair,
+        action: defai_staking::AdminAction,
+    ) -> Result<u64, BanksClientError> {
+        let admin_council: defai_staking::AdminCouncil = self.account(self.admin_council()).await;
+        let proposal_id = admin_council.proposal_count;
+
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::CreateAdminProposal {
+                program_state: self.program_state,
+                admin_council: self.admin_council(),
+                admin_proposal: self.admin_proposal(proposal_id),
+                proposer: proposer.pubkey(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::CreateAdminProposal { action }.data(),
+        };
+        self.send(&[instruction], &[proposer]).await?;
+
+        Ok(proposal_id)
+    }
+
+    pub async fn approve_admin_proposal(&mut self, approver: &Keypair, proposal_id: u64) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::ApproveAdminProposal {
+                program_state: self.program_state,
+                admin_council: self.admin_council(),
+                admin_proposal: self.admin_proposal(proposal_id),
+                approver: approver.pubkey(),
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::ApproveAdminProposal { proposal_id }.data(),
+        };
+        self.send(&[instruction], &[approver]).await
+    }
+
+    // Executes a proposal whose action only touches the program state, tier config or council
+    pub async fn execute_admin_proposal(&mut self, executor: &Keypair, proposal_id: u64) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::ExecuteAdminProposal {
+                program_state: self.program_state,
+                admin_council: self.admin_council(),
+                admin_proposal: self.admin_proposal(proposal_id),
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                penalty_config: None,
+                reward_escrow: None,
+                escrow_token_account: None,
+                destination_token_account: None,
+                defai_mint: None,
+                admin_roles: None,
+                treasury_token_account: None,
+                executor: executor.pubkey(),
+                token_program: None,
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::ExecuteAdminProposal { proposal_id }.data(),
+        };
+        self.send(&[instruction], &[executor]).await
+    }
+
+    pub async fn propose_escrow_withdrawal(&mut self, amount: u64, destination: Pubkey) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::ProposeEscrowWithdrawal {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            reward_escrow: self.reward_escrow,
+            authority: self.admin.pubkey(),
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::ProposeEscrowWithdrawal { amount, destination })
+            .await
+    }
+
+    pub async fn cancel_escrow_withdrawal(&mut self) -> Result<(), BanksClientError> {
+        let accounts = defai_staking::accounts::ProposeEscrowWithdrawal {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            reward_escrow: self.reward_escrow,
+            authority: self.admin.pubkey(),
+        };
+        self.send_as_admin(accounts, defai_staking::instruction::CancelEscrowWithdrawal {}).await
+    }
+
+    pub async fn execute_escrow_withdrawal(&mut self, destination: Pubkey) -> Result<(), BanksClientError> {
+        let instruction = self.instruction(
+            defai_staking::accounts::ExecuteEscrowWithdrawal {
+                program_state: self.program_state,
+                admin_roles: self.admin_roles,
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+                escrow_token_account: self.escrow_vault,
+                destination_token_account: destination,
+                defai_mint: self.mint,
+                authority: self.admin.pubkey(),
+                token_program: self.token_program,
+            },
+            defai_staking::instruction::ExecuteEscrowWithdrawal {},
+        );
+        let admin = self.admin.insecure_clone();
+        self.send(&[instruction], &[&admin]).await
+    }
+
+    // Permissionless: brings the reward indices up to date and re-evaluates the runway scale
+    pub async fn refresh_runway(&mut self) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::RefreshRunway {
+                program_state: self.program_state,
+                tier_config: self.tier_config,
+                reward_accrual: self.reward_accrual,
+                reward_escrow: self.reward_escrow,
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::RefreshRunway {}.data(),
+        };
+        self.send(&[instruction], &[]).await
+    }
+
+    pub async fn check_invariants(&mut self) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::CheckInvariants {
+                program_state: self.program_state,
+                reward_escrow: self.reward_escrow,
+                stake_vault: self.stake_vault,
+                escrow_token_account: self.escrow_vault,
+                defai_mint: self.mint,
+                caller: self.context.payer.pubkey(),
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::CheckInvariants {}.data(),
+        };
+        self.send(&[instruction], &[]).await
+    }
+
+    pub async fn set_auto_pause_on_deficit(&mut self, enabled: bool) {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::UpdateProgramConfig {
+                program_state: self.program_state,
+                admin_roles: self.admin_roles,
+                authority: self.admin.pubkey(),
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::SetAutoPauseOnDeficit { enabled }.data(),
+        };
+        let admin = self.admin.insecure_clone();
+        self.send(&[instruction], &[&admin]).await.unwrap();
+    }
+
+    pub async fn set_treasury(&mut self, treasury_token_account: Pubkey) {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: defai_staking::accounts::SetTreasury {
+                program_state: self.program_state,
+                treasury_token_account,
+                authority: self.admin.pubkey(),
+            }
+            .to_account_metas(None),
+            data: defai_staking::instruction::SetTreasury {}.data(),
+        };
+        let admin = self.admin.insecure_clone();
+        self.send(&[instruction], &[&admin]).await.unwrap();
+    }
+
+    // Keeps the penalty brackets, routing collected penalties per `split`
+    fn update_penalty_config_accounts(&self) -> defai_staking::accounts::UpdatePenaltyConfig {
+        defai_staking::accounts::UpdatePenaltyConfig {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            penalty_config: self.penalty_config,
+            authority: self.admin.pubkey(),
+        }
+    }
+
+    pub async fn propose_penalty_schedule(
+        &mut self,
+        brackets: Vec<defai_staking::PenaltyBracket>,
+        linear_decay: bool,
+        split: defai_staking::PenaltySplit,
+    ) -> Result<(), BanksClientError> {
+        let accounts = self.update_penalty_config_accounts();
+        self.send_as_admin(
+            accounts,
+            defai_staking::instruction::ProposePenaltyScheduleUpdate { brackets, linear_decay, split },
+        )
+        .await
+    }
+
+    pub async fn accept_penalty_schedule(&mut self) -> Result<(), BanksClientError> {
+        let accounts = self.update_penalty_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::AcceptPenaltyScheduleUpdate {}).await
+    }
+
+    pub async fn cancel_penalty_schedule(&mut self) -> Result<(), BanksClientError> {
+        let accounts = self.update_penalty_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::CancelPenaltyScheduleUpdate {}).await
+    }
+
+    // Proposes the schedule and accepts it once the timelock has passed
+    pub async fn set_penalty_schedule(
+        &mut self,
+        brackets: Vec<defai_staking::PenaltyBracket>,
+        linear_decay: bool,
+        split: defai_staking::PenaltySplit,
+    ) {
+        self.propose_penalty_schedule(brackets, linear_decay, split).await.unwrap();
+        self.warp(ADMIN_TIMELOCK_DURATION).await;
+        self.accept_penalty_schedule().await.unwrap();
+    }
+
+    pub async fn set_penalty_distribution(&mut self, distribute_to_stakers: bool) {
+        let accounts = self.update_penalty_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::SetPenaltyDistribution { distribute_to_stakers })
+            .await
+            .unwrap();
+    }
+
+    // Keeps the current brackets and only changes where penalties go
+    pub async fn set_penalty_split(&mut self, split: defai_staking::PenaltySplit) {
+        let penalty_config: defai_staking::PenaltyConfig = self.account(self.penalty_config).await;
+        self.set_penalty_schedule(penalty_config.brackets, penalty_config.linear_decay, split).await;
+    }
+
+    // Sends an admin instruction signed by the pool authority
+    pub async fn send_as_admin(
+        &mut self,
+        accounts: impl ToAccountMetas,
+        data: impl InstructionData,
+    ) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: defai_staking::ID,
+            accounts: accounts.to_account_metas(None),
+            data: data.data(),
+        };
+        let admin = self.admin.insecure_clone();
+        self.send(&[instruction], &[&admin]).await
+    }
+
+    fn update_tier_config_accounts(&self) -> defai_staking::accounts::UpdateTierConfig {
+        defai_staking::accounts::UpdateTierConfig {
+            program_state: self.program_state,
+            admin_roles: self.admin_roles,
+            tier_config: self.tier_config,
+            reward_accrual: self.reward_accrual,
+            reward_escrow: self.reward_escrow,
+            authority: self.admin.pubkey(),
+        }
+    }
+
+    pub async fn propose_tier_config(
+        &mut self,
+        tiers: Vec<defai_staking::TierInfo>,
+        lock_terms: Vec<defai_staking::LockTerm>,
+        base_apy_bps: u16,
+    ) -> Result<(), BanksClientError> {
+        let accounts = self.update_tier_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::ProposeTierConfigUpdate { tiers, lock_terms, base_apy_bps })
+            .await
+    }
+
+    pub async fn accept_tier_config(&mut self) -> Result<(), BanksClientError> {
+        let accounts = self.update_tier_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::AcceptTierConfigUpdate {}).await
+    }
+
+    pub async fn cancel_tier_config(&mut self) -> Result<(), BanksClientError> {
+        let accounts = self.update_tier_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::CancelTierConfigUpdate {}).await
+    }
+
+    // Proposes the table and accepts it once the timelock has passed
+    pub async fn set_tier_config(
+        &mut self,
+        tiers: Vec<defai_staking::TierInfo>,
+        lock_terms: Vec<defai_staking::LockTerm>,
+        base_apy_bps: u16,
+    ) {
+        self.propose_tier_config(tiers, lock_terms, base_apy_bps).await.unwrap();
+        self.warp(ADMIN_TIMELOCK_DURATION).await;
+        self.accept_tier_config().await.unwrap();
+    }
+
+    pub async fn set_min_runway_days(&mut self, min_runway_days: u16) {
+        let accounts = self.update_tier_config_accounts();
+        self.send_as_admin(accounts, defai_staking::instruction::SetMinRunwayDays { min_runway_days })
+            .await
+            .unwrap();
+    }
+
+    // Keeps the tier table and lock terms, changing only the APY of stakes below the lowest tier
+    pub async fn set_base_apy(&mut self, base_apy_bps: u16) {
+        let tier_config: defai_staking::TierConfig = self.account(self.tier_config).await;
+        self.set_tier_config(tier_config.tiers, tier_config.lock_terms, base_apy_bps).await;
+    }
+
+    // Asserts both vaults hold exactly what the program accounts for
+    pub async fn assert_vaults_reconciled(&mut self) {
+        let program_state: defai_staking::ProgramState = self.account(self.program_state).await;
+        let reward_escrow: defai_staking::RewardEscrow = self.account(self.reward_escrow).await;
+
+        assert_eq!(
+            self.token_balance(self.stake_vault).await,
+            program_state.total_staked + program_state.total_unbonding
+        );
+        assert_eq!(self.token_balance(self.escrow_vault).await, reward_escrow.total_balance);
+    }
+}
@@ -0,0 +1,75 @@
+// Mock transfer-hook program: counts Execute calls and the amounts moved in a writable account
+// listed in the mint's extra-account-metas, so tests can tell every transfer reached the hook.
+use solana_sdk::{
+    account::Account, account_info::AccountInfo, entrypoint::ProgramResult, program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::{ExecuteInstruction, TransferHookInstruction},
+};
+
+pub const HOOK_PROGRAM_ID: Pubkey = Pubkey::new_from_array([0x48; 32]);
+
+// Execute accounts: source, mint, destination, authority, validation, then the extra metas
+const VALIDATION_INDEX: usize = 4;
+const COUNTER_INDEX: usize = 5;
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let TransferHookInstruction::Execute { amount } = TransferHookInstruction::unpack(data)? else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    let mint = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let validation = accounts.get(VALIDATION_INDEX).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if *validation.key != get_extra_account_metas_address(mint.key, program_id) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    ExtraAccountMetaList::check_account_infos::<ExecuteInstruction>(
+        accounts,
+        data,
+        program_id,
+        &validation.try_borrow_data()?,
+    )?;
+
+    let counter = accounts.get(COUNTER_INDEX).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let mut counter_data = counter.try_borrow_mut_data()?;
+    let (calls, total) = read_counter(&counter_data);
+    counter_data[..8].copy_from_slice(&(calls + 1).to_le_bytes());
+    counter_data[8..16].copy_from_slice(&(total + amount).to_le_bytes());
+
+    Ok(())
+}
+
+// Returns (Execute calls, total amount transferred)
+pub fn read_counter(data: &[u8]) -> (u64, u64) {
+    (
+        u64::from_le_bytes(data[..8].try_into().unwrap()),
+        u64::from_le_bytes(data[8..16].try_into().unwrap()),
+    )
+}
+
+pub fn counter_account() -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data: vec![0; 16],
+        owner: HOOK_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// The validation account the token program reads to resolve the counter on every transfer
+pub fn validation_account(counter: &Pubkey) -> Account {
+    let extra_metas = [ExtraAccountMeta::new_with_pubkey(counter, false, true).unwrap()];
+    let mut data = vec![0; ExtraAccountMetaList::size_of(extra_metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &extra_metas).unwrap();
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: HOOK_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
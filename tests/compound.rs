@@ -0,0 +1,48 @@
+mod common;
+
+use common::{TestPool, ESCROW_FUNDING};
+use defai_staking::{ProgramState, UserStake, GOLD_MIN};
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn compound_keeps_stake_vault_equal_to_total_staked() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+
+    pool.warp_days(30).await;
+    pool.compound(&user, position_id).await.unwrap();
+
+    let user_stake: UserStake = pool.account(pool.user_stake(&user.pubkey(), position_id)).await;
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert!(user_stake.staked_amount > GOLD_MIN);
+    assert_eq!(program_state.total_staked, user_stake.staked_amount);
+    assert_eq!(pool.token_balance(pool.stake_vault).await, program_state.total_staked);
+    pool.assert_vaults_reconciled().await;
+}
+
+#[tokio::test]
+async fn compound_across_positions_stays_reconciled() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+
+    let (alice, alice_tokens) = pool.create_user(GOLD_MIN).await;
+    let (bob, bob_tokens) = pool.create_user(2 * GOLD_MIN).await;
+    let alice_position = pool.stake(&alice, alice_tokens, GOLD_MIN, 30).await.unwrap();
+    let bob_position = pool.stake(&bob, bob_tokens, 2 * GOLD_MIN, 0).await.unwrap();
+
+    // Compounding at different times, and twice for one position, must never drift from the vault
+    pool.warp_days(10).await;
+    pool.compound(&alice, alice_position).await.unwrap();
+    pool.warp_days(20).await;
+    pool.compound(&bob, bob_position).await.unwrap();
+    pool.compound(&alice, alice_position).await.unwrap();
+
+    let alice_stake: UserStake = pool.account(pool.user_stake(&alice.pubkey(), alice_position)).await;
+    let bob_stake: UserStake = pool.account(pool.user_stake(&bob.pubkey(), bob_position)).await;
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.total_staked, alice_stake.staked_amount + bob_stake.staked_amount);
+    pool.assert_vaults_reconciled().await;
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{assert_staking_error, TestPool, ESCROW_FUNDING};
+use defai_staking::{ProgramState, RewardAccrual, StakingError, UserStake, GOLD_MIN};
+use solana_sdk::signature::Signer;
+
+// Left behind below the lowest tier (1,000 DEFAI)
+const DUST: u64 = 1_000 * 10u64.pow(6);
+
+#[tokio::test]
+async fn dust_position_accrues_base_apy_through_claim_compound_and_exit() {
+    let mut pool = TestPool::new().await;
+    pool.set_base_apy(25).await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+    let user_stake_address = pool.user_stake(&user.pubkey(), position_id);
+
+    // Past the penalty schedule, so the partial unstake returns the full amount
+    pool.warp_days(91).await;
+    pool.unstake(&user, user_tokens, position_id, GOLD_MIN - DUST, None).await.unwrap();
+
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert_eq!(user_stake.staked_amount, DUST);
+    assert_eq!(user_stake.tier, 0);
+    let reward_accrual: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(reward_accrual.tier_weights[0], user_stake.reward_weight);
+    assert_eq!(reward_accrual.tier_weights[1], 0);
+    let earned_at_gold = user_stake.rewards_earned;
+
+    // A year at 0.25% on tier slot 0
+    pool.warp_days(365).await;
+    let balance_before = pool.token_balance(user_tokens).await;
+    pool.claim(&user, user_tokens, position_id).await.unwrap();
+
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert_eq!(user_stake.rewards_earned - earned_at_gold, DUST * 25 / 10_000);
+    assert_eq!(pool.token_balance(user_tokens).await - balance_before, user_stake.rewards_earned);
+    assert_eq!(user_stake.rewards_claimed, user_stake.rewards_earned);
+
+    // Compounding keeps the position in slot 0
+    pool.warp_days(30).await;
+    pool.compound(&user, position_id).await.unwrap();
+
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert!(user_stake.staked_amount > DUST);
+    assert_eq!(user_stake.tier, 0);
+    pool.assert_vaults_reconciled().await;
+
+    // Exiting the remainder empties the slot and the vault
+    let remainder = user_stake.staked_amount;
+    pool.unstake(&user, user_tokens, position_id, remainder, None).await.unwrap();
+
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    let reward_accrual: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(program_state.total_staked, 0);
+    assert_eq!(reward_accrual.tier_weights[0], 0);
+    assert_eq!(pool.token_balance(pool.stake_vault).await, 0);
+    pool.assert_vaults_reconciled().await;
+
+    pool.close_stake(&user, user_tokens, position_id).await.unwrap();
+    assert!(pool.context.banks_client.get_account(user_stake_address).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn dust_position_earns_nothing_without_base_apy() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+
+    pool.warp_days(91).await;
+    pool.unstake(&user, user_tokens, position_id, GOLD_MIN - DUST, None).await.unwrap();
+
+    // Rewards earned at the Gold tier are still paid out
+    pool.claim(&user, user_tokens, position_id).await.unwrap();
+
+    pool.warp_days(365).await;
+    assert_staking_error(pool.claim(&user, user_tokens, position_id).await, StakingError::NoRewards);
+    assert_staking_error(pool.compound(&user, position_id).await, StakingError::NoRewards);
+
+    pool.unstake(&user, user_tokens, position_id, DUST, None).await.unwrap();
+
+    let user_stake: UserStake = pool.account(pool.user_stake(&user.pubkey(), position_id)).await;
+    assert_eq!(pool.token_balance(user_tokens).await, GOLD_MIN + user_stake.rewards_claimed);
+    pool.assert_vaults_reconciled().await;
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::{assert_staking_error, index_growth, TestPool, ESCROW_FUNDING};
+use defai_staking::{
+    RewardAccrual, RewardEscrow, StakingError, ADMIN_TIMELOCK_DURATION, GOLD_APY_BPS, GOLD_MIN,
+    REWARD_INDEX_PRECISION,
+};
+use solana_sdk::signature::Signer;
+
+const GOLD: usize = 1;
+
+// Liabilities a lone unlocked Gold stake adds over `seconds` from `reward_accrual`'s last update
+fn gold_liabilities(reward_accrual: &RewardAccrual, seconds: i64) -> u64 {
+    let (growth, _) = index_growth(GOLD_APY_BPS, seconds, reward_accrual.index_remainders[GOLD]);
+    reward_accrual.accrued_liabilities + (GOLD_MIN as u128 * growth / REWARD_INDEX_PRECISION) as u64
+}
+
+#[tokio::test]
+async fn only_the_surplus_above_accrued_liabilities_can_be_withdrawn() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+    let destination = pool.create_token_account(&pool.admin.pubkey()).await;
+
+    // One unit more than the surplus at execution time is rejected
+    let staked: RewardAccrual = pool.account(pool.reward_accrual).await;
+    let liabilities = gold_liabilities(&staked, ADMIN_TIMELOCK_DURATION);
+    pool.propose_escrow_withdrawal(ESCROW_FUNDING - liabilities + 1, destination).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    assert_staking_error(
+        pool.execute_escrow_withdrawal(destination).await,
+        StakingError::WithdrawalExceedsSurplus,
+    );
+    pool.refresh_runway().await.unwrap();
+    let refreshed: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(refreshed.accrued_liabilities, liabilities);
+    pool.cancel_escrow_withdrawal().await.unwrap();
+
+    // Exactly the surplus leaves the escrow holding what stakers have accrued
+    let liabilities = gold_liabilities(&refreshed, ADMIN_TIMELOCK_DURATION);
+    pool.propose_escrow_withdrawal(ESCROW_FUNDING - liabilities, destination).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION).await;
+    pool.execute_escrow_withdrawal(destination).await.unwrap();
+    assert_eq!(pool.token_balance(destination).await, ESCROW_FUNDING - liabilities);
+    let reward_escrow: RewardEscrow = pool.account(pool.reward_escrow).await;
+    let reward_accrual: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(reward_escrow.total_balance, liabilities);
+    assert_eq!(reward_accrual.accrued_liabilities, liabilities);
+    assert!(reward_escrow.pending_withdrawal_destination.is_none());
+
+    // and the staker can still claim all of it
+    pool.claim(&user, user_tokens, position_id).await.unwrap();
+    assert_eq!(pool.token_balance(user_tokens).await, liabilities);
+    let reward_escrow: RewardEscrow = pool.account(pool.reward_escrow).await;
+    assert_eq!(reward_escrow.total_balance, 0);
+    pool.assert_vaults_reconciled().await;
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{TestPool, ESCROW_FUNDING};
+use defai_staking::{RewardAccrual, UserStake, GOLD_MIN, REWARD_INDEX_PRECISION};
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn distributed_penalty_is_shared_by_the_other_stakers_only() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    pool.set_penalty_distribution(true).await;
+
+    let (alice, alice_tokens) = pool.create_user(GOLD_MIN).await;
+    let (bob, bob_tokens) = pool.create_user(3 * GOLD_MIN).await;
+    let (carol, carol_tokens) = pool.create_user(2 * GOLD_MIN).await;
+    let alice_position = pool.stake(&alice, alice_tokens, GOLD_MIN, 0).await.unwrap();
+    let bob_position = pool.stake(&bob, bob_tokens, 3 * GOLD_MIN, 0).await.unwrap();
+    let carol_position = pool.stake(&carol, carol_tokens, 2 * GOLD_MIN, 0).await.unwrap();
+
+    // Carol leaves half her stake early at the 2% rate; only Alice's and Bob's 4 * GOLD_MIN share it
+    pool.warp_days(10).await;
+    let before: RewardAccrual = pool.account(pool.reward_accrual).await;
+    pool.unstake(&carol, carol_tokens, carol_position, GOLD_MIN, None).await.unwrap();
+    let penalty = GOLD_MIN * 200 / 10_000;
+    let after: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(
+        after.bonus_per_share - before.bonus_per_share,
+        penalty as u128 * REWARD_INDEX_PRECISION / (4 * GOLD_MIN) as u128
+    );
+
+    let mut bonuses = Vec::new();
+    for (user, user_tokens, position_id) in [
+        (&alice, alice_tokens, alice_position),
+        (&bob, bob_tokens, bob_position),
+        (&carol, carol_tokens, carol_position),
+    ] {
+        pool.claim(user, user_tokens, position_id).await.unwrap();
+        let user_stake: UserStake = pool.account(pool.user_stake(&user.pubkey(), position_id)).await;
+        assert_eq!(user_stake.bonus_claimed, user_stake.bonus_earned);
+        bonuses.push(user_stake.bonus_claimed);
+    }
+    assert_eq!(bonuses, [penalty / 4, penalty * 3 / 4, 0]);
+    pool.assert_vaults_reconciled().await;
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{assert_staking_error, TestPool};
+use defai_staking::{
+    PenaltyBracket, PenaltyConfig, PenaltySplit, StakingError, ADMIN_TIMELOCK_DURATION, GOLD_MIN, SECONDS_PER_DAY,
+};
+
+#[tokio::test]
+async fn pending_penalty_change_must_be_cancelled_before_another_is_proposed() {
+    let mut pool = TestPool::new().await;
+    let penalty_config: PenaltyConfig = pool.account(pool.penalty_config).await;
+    let (brackets, split) = (penalty_config.brackets, penalty_config.split);
+
+    pool.propose_penalty_schedule(brackets.clone(), true, split).await.unwrap();
+    assert_staking_error(
+        pool.propose_penalty_schedule(brackets.clone(), false, split).await,
+        StakingError::PenaltyChangeAlreadyPending,
+    );
+
+    pool.cancel_penalty_schedule().await.unwrap();
+    let penalty_config: PenaltyConfig = pool.account(pool.penalty_config).await;
+    assert!(penalty_config.pending_brackets.is_none());
+    assert!(!penalty_config.pending_linear_decay);
+    assert_eq!(penalty_config.pending_split, PenaltySplit::default());
+    assert_eq!(penalty_config.penalty_change_timestamp, 0);
+    assert_staking_error(pool.cancel_penalty_schedule().await, StakingError::NoPendingPenaltyChange);
+    assert_staking_error(pool.accept_penalty_schedule().await, StakingError::NoPendingPenaltyChange);
+
+    // The replacement runs its own timelock
+    let replacement = vec![PenaltyBracket { min_days: 0, penalty_bps: 500 }];
+    pool.propose_penalty_schedule(replacement.clone(), false, split).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION - 60).await;
+    assert_staking_error(pool.accept_penalty_schedule().await, StakingError::TimelockNotExpired);
+    pool.warp(60).await;
+    pool.accept_penalty_schedule().await.unwrap();
+
+    let penalty_config: PenaltyConfig = pool.account(pool.penalty_config).await;
+    assert_eq!(penalty_config.brackets, replacement);
+    assert!(!penalty_config.linear_decay);
+    assert!(penalty_config.pending_brackets.is_none());
+}
+
+#[tokio::test]
+async fn linear_decay_slides_the_penalty_between_brackets_by_the_second() {
+    let mut pool = TestPool::new().await;
+    let penalty_config: PenaltyConfig = pool.account(pool.penalty_config).await;
+    pool.set_penalty_schedule(penalty_config.brackets, true, penalty_config.split).await;
+
+    let (user, user_tokens) = pool.create_user(4 * GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, 4 * GOLD_MIN, 0).await.unwrap();
+
+    // (seconds since staking, expected bps) against the 2% / 1% at 30 days / free at 90 days launch curve
+    let checkpoints = [
+        (15 * SECONDS_PER_DAY, 150),
+        (20 * SECONDS_PER_DAY + SECONDS_PER_DAY / 2, 132), // 200 - 68.33, truncated toward the higher rate
+        (30 * SECONDS_PER_DAY, 100),
+        (60 * SECONDS_PER_DAY, 50),
+        (90 * SECONDS_PER_DAY - 1, 1),
+        (90 * SECONDS_PER_DAY, 0),
+        (120 * SECONDS_PER_DAY, 0),
+    ];
+    let amount = GOLD_MIN / 10;
+    let mut elapsed = 0;
+    for (at, penalty_bps) in checkpoints {
+        pool.warp(at - elapsed).await;
+        elapsed = at;
+
+        let balance_before = pool.token_balance(user_tokens).await;
+        pool.unstake(&user, user_tokens, position_id, amount, None).await.unwrap();
+        let penalty = amount - (pool.token_balance(user_tokens).await - balance_before);
+        assert_eq!(penalty, amount * penalty_bps / 10_000, "penalty after {at} seconds");
+    }
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{index_growth, TestPool, ESCROW_FUNDING};
+use defai_staking::{
+    RewardAccrual, BASIS_POINTS, GOLD_APY_BPS, GOLD_MIN, REWARD_INDEX_PRECISION, SECONDS_PER_DAY,
+    TITANIUM_APY_BPS, TITANIUM_MIN,
+};
+
+const GOLD: usize = 1;
+const TITANIUM: usize = 2;
+
+// One whole DEFAI per second for 10 days, but only budgeted for 5
+const EMISSION_PER_SECOND: u64 = 1_000_000;
+const BUDGET: u64 = 5 * SECONDS_PER_DAY as u64 * EMISSION_PER_SECOND;
+
+#[tokio::test]
+async fn campaign_emits_to_eligible_tiers_until_its_budget_runs_out() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let (alice, alice_tokens) = pool.create_user(GOLD_MIN).await;
+    let (bob, bob_tokens) = pool.create_user(TITANIUM_MIN).await;
+    let (carol, carol_tokens) = pool.create_user(TITANIUM_MIN).await;
+    pool.stake(&alice, alice_tokens, GOLD_MIN, 0).await.unwrap();
+    let bob_position = pool.stake(&bob, bob_tokens, TITANIUM_MIN, 0).await.unwrap();
+    pool.stake(&carol, carol_tokens, TITANIUM_MIN, 0).await.unwrap();
+
+    let now = pool.now().await;
+    pool.create_reward_campaign(now, now + 10 * SECONDS_PER_DAY, 1 << TITANIUM, EMISSION_PER_SECOND, BUDGET)
+        .await
+        .unwrap();
+    let start: RewardAccrual = pool.account(pool.reward_accrual).await;
+
+    // Titanium's index gains the emission spread over both Titanium stakes on top of its APY; Gold's does not
+    let titanium_weight = 2 * TITANIUM_MIN as u128 * BASIS_POINTS as u128;
+    let campaign_growth =
+        |emitted: u64| emitted as u128 * REWARD_INDEX_PRECISION * BASIS_POINTS as u128 / titanium_weight;
+    pool.warp_days(2).await;
+    pool.refresh_runway().await.unwrap();
+    let emitted = 2 * SECONDS_PER_DAY as u64 * EMISSION_PER_SECOND;
+    let midway: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(midway.campaigns[0].distributed, emitted);
+    assert_eq!(
+        midway.reward_indices[TITANIUM] - start.reward_indices[TITANIUM],
+        index_growth(TITANIUM_APY_BPS, 2 * SECONDS_PER_DAY, start.index_remainders[TITANIUM]).0
+            + campaign_growth(emitted)
+    );
+    assert_eq!(
+        midway.reward_indices[GOLD] - start.reward_indices[GOLD],
+        index_growth(GOLD_APY_BPS, 2 * SECONDS_PER_DAY, start.index_remainders[GOLD]).0
+    );
+
+    // The budget runs out on day 5; by day 8 the campaign has been dropped, days before its end time
+    pool.warp_days(6).await;
+    pool.refresh_runway().await.unwrap();
+    let ended: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert!(ended.campaigns.is_empty());
+    let titanium_growth = ended.reward_indices[TITANIUM] - start.reward_indices[TITANIUM];
+    assert_eq!(
+        titanium_growth,
+        index_growth(TITANIUM_APY_BPS, 8 * SECONDS_PER_DAY, start.index_remainders[TITANIUM]).0
+            + campaign_growth(emitted)
+            + campaign_growth(BUDGET - emitted)
+    );
+
+    // Bob is paid his APY plus exactly half the budget
+    pool.claim(&bob, bob_tokens, bob_position).await.unwrap();
+    let apy_rewards = (TITANIUM_MIN as u128
+        * index_growth(TITANIUM_APY_BPS, 8 * SECONDS_PER_DAY, start.index_remainders[TITANIUM]).0
+        / REWARD_INDEX_PRECISION) as u64;
+    assert_eq!(pool.token_balance(bob_tokens).await, apy_rewards + BUDGET / 2);
+    pool.assert_vaults_reconciled().await;
+}
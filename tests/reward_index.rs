@@ -0,0 +1,82 @@
+mod common;
+
+use common::{index_growth, TestPool, ESCROW_FUNDING};
+use defai_staking::{
+    RewardAccrual, TierConfig, UserStake, BASIS_POINTS, GOLD_APY_BPS, GOLD_MIN, REWARD_INDEX_PRECISION,
+    SECONDS_PER_DAY, TITANIUM_APY_BPS, TITANIUM_MIN,
+};
+use solana_sdk::signature::Signer;
+
+const GOLD: usize = 1;
+const TITANIUM: usize = 2;
+
+#[tokio::test]
+async fn indices_grow_per_tier_and_carry_truncated_remainders() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let (alice, alice_tokens) = pool.create_user(GOLD_MIN).await;
+    let (bob, bob_tokens) = pool.create_user(TITANIUM_MIN).await;
+    let alice_position = pool.stake(&alice, alice_tokens, GOLD_MIN, 0).await.unwrap();
+    let bob_position = pool.stake(&bob, bob_tokens, TITANIUM_MIN, 0).await.unwrap();
+    let start: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(start.reward_scale_bps, BASIS_POINTS as u16);
+
+    // Twenty 7-second updates each truncate the index, but the carried remainders add back up
+    for _ in 0..20 {
+        pool.warp(7).await;
+        pool.refresh_runway().await.unwrap();
+    }
+    let end: RewardAccrual = pool.account(pool.reward_accrual).await;
+    for (tier, apy_bps) in [(GOLD, GOLD_APY_BPS), (TITANIUM, TITANIUM_APY_BPS)] {
+        let (growth, remainder) = index_growth(apy_bps, 140, start.index_remainders[tier]);
+        assert_eq!(end.reward_indices[tier] - start.reward_indices[tier], growth);
+        assert_eq!(end.index_remainders[tier], remainder);
+    }
+
+    // Each position accrues against its own tier's index
+    pool.compound(&alice, alice_position).await.unwrap();
+    pool.compound(&bob, bob_position).await.unwrap();
+    let end: RewardAccrual = pool.account(pool.reward_accrual).await;
+    let alice_stake: UserStake = pool.account(pool.user_stake(&alice.pubkey(), alice_position)).await;
+    let bob_stake: UserStake = pool.account(pool.user_stake(&bob.pubkey(), bob_position)).await;
+    let gold_growth = end.reward_indices[GOLD] - start.reward_indices[GOLD];
+    let titanium_growth = end.reward_indices[TITANIUM] - start.reward_indices[TITANIUM];
+    assert_eq!(alice_stake.staked_amount - GOLD_MIN, (GOLD_MIN as u128 * gold_growth / REWARD_INDEX_PRECISION) as u64);
+    assert_eq!(
+        bob_stake.staked_amount - TITANIUM_MIN,
+        (TITANIUM_MIN as u128 * titanium_growth / REWARD_INDEX_PRECISION) as u64
+    );
+}
+
+#[tokio::test]
+async fn apy_change_only_applies_going_forward() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+    let staked: RewardAccrual = pool.account(pool.reward_accrual).await;
+
+    // Doubling the Gold APY takes a timelock, during which the old rate keeps accruing
+    pool.warp_days(30).await;
+    let mut tier_config: TierConfig = pool.account(pool.tier_config).await;
+    tier_config.tiers[GOLD - 1].apy_bps = 2 * GOLD_APY_BPS;
+    pool.set_tier_config(tier_config.tiers, tier_config.lock_terms, tier_config.base_apy_bps).await;
+    let accepted: RewardAccrual = pool.account(pool.reward_accrual).await;
+    let elapsed = accepted.last_index_update - staked.last_index_update;
+    assert_eq!(
+        (accepted.reward_indices[GOLD] - staked.reward_indices[GOLD], accepted.index_remainders[GOLD]),
+        index_growth(GOLD_APY_BPS, elapsed, staked.index_remainders[GOLD])
+    );
+
+    pool.warp_days(30).await;
+    pool.claim(&user, user_tokens, position_id).await.unwrap();
+    let claimed: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(
+        (claimed.reward_indices[GOLD] - accepted.reward_indices[GOLD], claimed.index_remainders[GOLD]),
+        index_growth(2 * GOLD_APY_BPS, 30 * SECONDS_PER_DAY, accepted.index_remainders[GOLD])
+    );
+
+    let growth = claimed.reward_indices[GOLD] - staked.reward_indices[GOLD];
+    let rewards = (GOLD_MIN as u128 * growth / REWARD_INDEX_PRECISION) as u64;
+    assert_eq!(pool.token_balance(user_tokens).await, rewards);
+}
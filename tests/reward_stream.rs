@@ -0,0 +1,99 @@
+mod common;
+
+use common::{assert_staking_error, TestPool, ESCROW_FUNDING};
+use defai_staking::{
+    RewardStream, StakingError, ADMIN_TIMELOCK_DURATION, GOLD_MIN, MAX_TIERS, PAUSE_STAKE, PAUSE_UNSTAKE,
+    REWARD_INDEX_PRECISION, SECONDS_PER_DAY, SECONDS_PER_YEAR,
+};
+use solana_sdk::signature::Signer;
+
+// Every tier earns one whole reward token per second on a GOLD_MIN stake
+const REWARD_PER_SECOND: u64 = 1_000_000;
+const STREAM_FUNDING: u64 = 5_000_000 * REWARD_PER_SECOND;
+
+fn stream_rate() -> u64 {
+    (REWARD_PER_SECOND as u128 * SECONDS_PER_YEAR as u128 * REWARD_INDEX_PRECISION / GOLD_MIN as u128) as u64
+}
+
+#[tokio::test]
+async fn stream_stops_accruing_at_close_and_is_swept_only_after_positions_settle() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let reward_mint = pool.create_reward_mint().await;
+    let stream_id = pool.create_reward_stream(reward_mint, vec![stream_rate(); MAX_TIERS + 1]).await.unwrap();
+    pool.fund_reward_stream(stream_id, STREAM_FUNDING).await.unwrap();
+
+    let (alice, alice_tokens) = pool.create_user(GOLD_MIN).await;
+    let (bob, bob_tokens) = pool.create_user(GOLD_MIN).await;
+    let alice_position = pool.stake(&alice, alice_tokens, GOLD_MIN, 0).await.unwrap();
+    let bob_position = pool.stake(&bob, bob_tokens, GOLD_MIN, 0).await.unwrap();
+    let alice_rewards = pool.create_token_account_for_mint(reward_mint, &alice.pubkey()).await;
+    let bob_rewards = pool.create_token_account_for_mint(reward_mint, &bob.pubkey()).await;
+
+    // Joining is paused along with staking
+    pool.pause(PAUSE_STAKE).await.unwrap();
+    assert_staking_error(
+        pool.join_reward_stream(&alice, stream_id, alice_position).await,
+        StakingError::ProgramPaused,
+    );
+    pool.pause(0).await.unwrap();
+    pool.join_reward_stream(&alice, stream_id, alice_position).await.unwrap();
+    pool.join_reward_stream(&bob, stream_id, bob_position).await.unwrap();
+    let reward_stream: RewardStream = pool.account(pool.reward_stream(stream_id)).await;
+    assert_eq!(reward_stream.participants, 2);
+
+    pool.warp_days(10).await;
+    pool.claim_reward_stream(&alice, alice_rewards, stream_id, alice_position).await.unwrap();
+    assert_eq!(pool.token_balance(alice_rewards).await, 10 * SECONDS_PER_DAY as u64 * REWARD_PER_SECOND);
+
+    // A closing stream takes no new participants
+    pool.propose_reward_stream_close(stream_id).await.unwrap();
+    let (carol, carol_tokens) = pool.create_user(GOLD_MIN).await;
+    let carol_position = pool.stake(&carol, carol_tokens, GOLD_MIN, 0).await.unwrap();
+    assert_staking_error(
+        pool.join_reward_stream(&carol, stream_id, carol_position).await,
+        StakingError::RewardStreamClosing,
+    );
+
+    // Nothing accrues past the close, however late the claim
+    pool.warp_days(30).await;
+    let accrued_until_close = (10 * SECONDS_PER_DAY + ADMIN_TIMELOCK_DURATION) as u64 * REWARD_PER_SECOND;
+    pool.claim_reward_stream(&alice, alice_rewards, stream_id, alice_position).await.unwrap();
+    assert_eq!(pool.token_balance(alice_rewards).await, accrued_until_close);
+
+    // Bob is still owed his share, so the vault cannot be swept yet
+    let destination = pool.create_token_account_for_mint(reward_mint, &pool.admin.pubkey()).await;
+    assert_staking_error(
+        pool.close_reward_stream(stream_id, destination).await,
+        StakingError::RewardStreamParticipantsRemaining,
+    );
+
+    // Leaving is paused along with unstaking
+    pool.pause(PAUSE_UNSTAKE).await.unwrap();
+    assert_staking_error(
+        pool.leave_reward_stream(&alice, stream_id, alice_position).await,
+        StakingError::ProgramPaused,
+    );
+    pool.pause(0).await.unwrap();
+    pool.leave_reward_stream(&alice, stream_id, alice_position).await.unwrap();
+
+    // Claiming base rewards moves Bob's stake-seconds past the close; settling prorates them back
+    pool.claim(&bob, bob_tokens, bob_position).await.unwrap();
+    pool.settle_reward_stream(&bob.pubkey(), bob_rewards, stream_id, bob_position).await.unwrap();
+    assert_eq!(pool.token_balance(bob_rewards).await, accrued_until_close);
+    assert!(pool
+        .context
+        .banks_client
+        .get_account(pool.stream_position(stream_id, &bob.pubkey(), bob_position))
+        .await
+        .unwrap()
+        .is_none());
+
+    pool.close_reward_stream(stream_id, destination).await.unwrap();
+    assert_eq!(pool.token_balance(destination).await, STREAM_FUNDING - 2 * accrued_until_close);
+    assert_eq!(pool.token_balance(pool.stream_vault(stream_id)).await, 0);
+    let reward_stream: RewardStream = pool.account(pool.reward_stream(stream_id)).await;
+    assert_eq!(reward_stream.participants, 0);
+    assert_eq!(reward_stream.total_balance, 0);
+    assert_eq!(reward_stream.total_distributed, 2 * accrued_until_close);
+}
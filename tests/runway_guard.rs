@@ -0,0 +1,48 @@
+mod common;
+
+use common::{scaled_index_growth, TestPool, ESCROW_FUNDING};
+use defai_staking::{
+    RewardAccrual, BASIS_POINTS, GOLD_APY_BPS, GOLD_MIN, REWARD_INDEX_PRECISION, SECONDS_PER_DAY, SECONDS_PER_YEAR,
+};
+
+const GOLD: usize = 1;
+const MIN_RUNWAY_DAYS: u16 = 100;
+
+// Full-APY accrual of a lone unlocked Gold stake over the minimum runway
+fn projected_liabilities() -> u64 {
+    let accrual_rate = GOLD_MIN as u128 * BASIS_POINTS as u128 * GOLD_APY_BPS as u128;
+    let rate_divisor = SECONDS_PER_YEAR as u128 * BASIS_POINTS as u128 * BASIS_POINTS as u128;
+    (accrual_rate * (MIN_RUNWAY_DAYS as i64 * SECONDS_PER_DAY) as u128 / rate_divisor) as u64
+}
+
+#[tokio::test]
+async fn accrual_is_scaled_to_the_escrow_runway() {
+    let mut pool = TestPool::new().await;
+    let funding = projected_liabilities() / 2;
+    pool.fund_escrow(funding).await.unwrap();
+    pool.set_min_runway_days(MIN_RUNWAY_DAYS).await;
+
+    // Half the runway funded accrues at half the APY
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+    let staked: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(staked.reward_scale_bps, 5_000);
+
+    pool.warp_days(10).await;
+    pool.refresh_runway().await.unwrap();
+    let refreshed: RewardAccrual = pool.account(pool.reward_accrual).await;
+    let (growth, _) = scaled_index_growth(GOLD_APY_BPS, 5_000, 10 * SECONDS_PER_DAY, staked.index_remainders[GOLD]);
+    assert_eq!(refreshed.reward_indices[GOLD] - staked.reward_indices[GOLD], growth);
+    let liabilities = (GOLD_MIN as u128 * growth / REWARD_INDEX_PRECISION) as u64;
+    assert_eq!(refreshed.accrued_liabilities, liabilities);
+
+    // What has accrued is no longer available, so the scale keeps dropping pro-rata
+    let scale_bps = (funding - liabilities) as u128 * BASIS_POINTS as u128 / projected_liabilities() as u128;
+    assert_eq!(refreshed.reward_scale_bps as u128, scale_bps);
+    assert!(refreshed.reward_scale_bps < 5_000);
+
+    // Topping up the escrow restores full APY
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let funded: RewardAccrual = pool.account(pool.reward_accrual).await;
+    assert_eq!(funded.reward_scale_bps, BASIS_POINTS as u16);
+}
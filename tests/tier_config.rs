@@ -0,0 +1,35 @@
+mod common;
+
+use common::{assert_staking_error, TestPool};
+use defai_staking::{StakingError, TierConfig, ADMIN_TIMELOCK_DURATION};
+
+#[tokio::test]
+async fn pending_tier_change_must_be_cancelled_before_another_is_proposed() {
+    let mut pool = TestPool::new().await;
+    let tier_config: TierConfig = pool.account(pool.tier_config).await;
+    let (tiers, lock_terms) = (tier_config.tiers, tier_config.lock_terms);
+
+    pool.propose_tier_config(tiers.clone(), lock_terms.clone(), 10).await.unwrap();
+    assert_staking_error(
+        pool.propose_tier_config(tiers.clone(), lock_terms.clone(), 20).await,
+        StakingError::TierConfigChangeAlreadyPending,
+    );
+
+    pool.cancel_tier_config().await.unwrap();
+    let tier_config: TierConfig = pool.account(pool.tier_config).await;
+    assert!(tier_config.pending_tiers.is_none());
+    assert_eq!(tier_config.tier_change_timestamp, 0);
+    assert_staking_error(pool.cancel_tier_config().await, StakingError::NoPendingTierConfigChange);
+    assert_staking_error(pool.accept_tier_config().await, StakingError::NoPendingTierConfigChange);
+
+    // The replacement runs its own timelock
+    pool.propose_tier_config(tiers, lock_terms, 20).await.unwrap();
+    pool.warp(ADMIN_TIMELOCK_DURATION - 60).await;
+    assert_staking_error(pool.accept_tier_config().await, StakingError::TimelockNotExpired);
+    pool.warp(60).await;
+    pool.accept_tier_config().await.unwrap();
+
+    let tier_config: TierConfig = pool.account(pool.tier_config).await;
+    assert_eq!(tier_config.base_apy_bps, 20);
+    assert!(tier_config.pending_tiers.is_none());
+}
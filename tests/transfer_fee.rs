@@ -0,0 +1,73 @@
+mod common;
+
+use common::{MintKind, TestPool, ESCROW_FUNDING};
+use defai_staking::{ProgramState, RewardEscrow, UserStake, GOLD_MIN};
+use solana_sdk::signature::Signer;
+
+const FEE_BASIS_POINTS: u16 = 100;
+
+// Token-2022 withholds ceil(amount * bps / 10_000) from every transfer
+fn transfer_fee(amount: u64) -> u64 {
+    ((amount as u128 * FEE_BASIS_POINTS as u128).div_ceil(10_000)) as u64
+}
+
+async fn fee_pool() -> TestPool {
+    TestPool::with_mint(MintKind::TransferFee {
+        basis_points: FEE_BASIS_POINTS,
+        maximum_fee: u64::MAX,
+    })
+    .await
+}
+
+#[tokio::test]
+async fn fee_mint_credits_net_amounts_on_fund_stake_and_compound() {
+    let mut pool = fee_pool().await;
+
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let escrow: RewardEscrow = pool.account(pool.reward_escrow).await;
+    assert_eq!(escrow.total_balance, ESCROW_FUNDING - transfer_fee(ESCROW_FUNDING));
+    assert_eq!(pool.token_balance(pool.escrow_vault).await, escrow.total_balance);
+
+    let amount = 2 * GOLD_MIN;
+    let (user, user_tokens) = pool.create_user(amount).await;
+    let position_id = pool.stake(&user, user_tokens, amount, 0).await.unwrap();
+    let user_stake: UserStake = pool.account(pool.user_stake(&user.pubkey(), position_id)).await;
+    let staked = amount - transfer_fee(amount);
+    assert_eq!(user_stake.staked_amount, staked);
+    assert_eq!(pool.token_balance(pool.stake_vault).await, staked);
+
+    // The escrow pays the gross reward, the position is credited what reaches the vault
+    pool.warp_days(30).await;
+    pool.compound(&user, position_id).await.unwrap();
+    let escrow_after: RewardEscrow = pool.account(pool.reward_escrow).await;
+    let rewards = escrow.total_balance - escrow_after.total_balance;
+    let user_stake: UserStake = pool.account(pool.user_stake(&user.pubkey(), position_id)).await;
+    assert!(rewards > 0);
+    assert_eq!(user_stake.staked_amount, staked + rewards - transfer_fee(rewards));
+
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.total_staked, user_stake.staked_amount);
+    pool.assert_vaults_reconciled().await;
+}
+
+#[tokio::test]
+async fn fee_mint_passes_invariant_check() {
+    let mut pool = fee_pool().await;
+    pool.set_auto_pause_on_deficit(true).await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+
+    let (alice, alice_tokens) = pool.create_user(GOLD_MIN * 3).await;
+    let (bob, bob_tokens) = pool.create_user(GOLD_MIN * 2).await;
+    let alice_position = pool.stake(&alice, alice_tokens, GOLD_MIN * 3, 30).await.unwrap();
+    let bob_position = pool.stake(&bob, bob_tokens, GOLD_MIN * 2, 0).await.unwrap();
+
+    pool.warp_days(45).await;
+    pool.compound(&alice, alice_position).await.unwrap();
+    pool.claim(&bob, bob_tokens, bob_position).await.unwrap();
+
+    // A deficit would trip auto-pause, so clean flags mean the books match the vaults
+    pool.check_invariants().await.unwrap();
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.pause_flags, 0);
+    pool.assert_vaults_reconciled().await;
+}
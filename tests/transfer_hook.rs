@@ -0,0 +1,59 @@
+mod common;
+
+use common::{MintKind, TestPool, ESCROW_FUNDING};
+use defai_staking::{PenaltySplit, RewardEscrow, UserStake, GOLD_MIN};
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn hook_runs_on_fund_stake_claim_and_compound() {
+    let mut pool = TestPool::with_mint(MintKind::TransferHook).await;
+
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    assert_eq!(pool.hook_calls().await, (1, ESCROW_FUNDING));
+
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+    assert_eq!(pool.hook_calls().await, (2, ESCROW_FUNDING + GOLD_MIN));
+
+    pool.warp_days(30).await;
+    pool.claim(&user, user_tokens, position_id).await.unwrap();
+    let claimed = pool.token_balance(user_tokens).await;
+    assert!(claimed > 0);
+    assert_eq!(pool.hook_calls().await, (3, ESCROW_FUNDING + GOLD_MIN + claimed));
+
+    pool.warp_days(30).await;
+    pool.compound(&user, position_id).await.unwrap();
+    let user_stake: UserStake = pool.account(pool.user_stake(&user.pubkey(), position_id)).await;
+    let compounded = user_stake.staked_amount - GOLD_MIN;
+    assert_eq!(pool.hook_calls().await, (4, ESCROW_FUNDING + GOLD_MIN + claimed + compounded));
+
+    pool.assert_vaults_reconciled().await;
+}
+
+#[tokio::test]
+async fn unstake_forwards_hook_accounts_to_every_penalty_transfer() {
+    let mut pool = TestPool::with_mint(MintKind::TransferHook).await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+
+    let treasury = pool.create_token_account(&pool.admin.pubkey()).await;
+    pool.set_treasury(treasury).await;
+    pool.set_penalty_split(PenaltySplit { escrow_bps: 5_000, treasury_bps: 5_000, burn_bps: 0 }).await;
+
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+
+    // Inside the 2% bracket, so one remaining-accounts list serves the user, escrow and treasury transfers
+    pool.warp_days(10).await;
+    let (calls_before, amount_before) = pool.hook_calls().await;
+    let escrow_before: RewardEscrow = pool.account(pool.reward_escrow).await;
+    pool.unstake(&user, user_tokens, position_id, GOLD_MIN, Some(treasury)).await.unwrap();
+
+    let penalty = GOLD_MIN * 200 / 10_000;
+    let escrow_after: RewardEscrow = pool.account(pool.reward_escrow).await;
+    assert_eq!(pool.token_balance(user_tokens).await, GOLD_MIN - penalty);
+    assert_eq!(pool.token_balance(treasury).await, penalty / 2);
+    assert_eq!(escrow_after.total_balance - escrow_before.total_balance, penalty / 2);
+    assert_eq!(pool.hook_calls().await, (calls_before + 3, amount_before + GOLD_MIN));
+
+    pool.assert_vaults_reconciled().await;
+}
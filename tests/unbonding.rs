@@ -0,0 +1,57 @@
+mod common;
+
+use common::{assert_staking_error, TestPool, ESCROW_FUNDING};
+use defai_staking::{ProgramState, StakingError, UserStake, GOLD_MIN};
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn stake_cannot_close_while_unbonding_tickets_are_outstanding() {
+    let mut pool = TestPool::new().await;
+    pool.fund_escrow(ESCROW_FUNDING).await.unwrap();
+    let (user, user_tokens) = pool.create_user(GOLD_MIN).await;
+    let position_id = pool.stake(&user, user_tokens, GOLD_MIN, 0).await.unwrap();
+    let user_stake_address = pool.user_stake(&user.pubkey(), position_id);
+    pool.warp_days(8).await;
+
+    // A cancelled ticket goes back into the position
+    let ticket_id = pool.request_unstake(&user, position_id, GOLD_MIN).await.unwrap();
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert_eq!(user_stake.open_tickets, 1);
+    assert_staking_error(
+        pool.close_stake(&user, user_tokens, position_id).await,
+        StakingError::UnbondingTicketsOpen,
+    );
+    pool.cancel_unstake(&user, position_id, ticket_id).await.unwrap();
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert_eq!(user_stake.open_tickets, 0);
+    assert_eq!(user_stake.staked_amount, GOLD_MIN);
+
+    // Split the whole position over two tickets
+    let first = pool.request_unstake(&user, position_id, GOLD_MIN / 2).await.unwrap();
+    let second = pool.request_unstake(&user, position_id, GOLD_MIN - GOLD_MIN / 2).await.unwrap();
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert_eq!(user_stake.staked_amount, 0);
+    assert_eq!(user_stake.open_tickets, 2);
+
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    pool.warp(program_state.unbonding_period).await;
+    pool.complete_unstake(&user, user_tokens, position_id, first).await.unwrap();
+    assert_staking_error(
+        pool.close_stake(&user, user_tokens, position_id).await,
+        StakingError::UnbondingTicketsOpen,
+    );
+
+    pool.complete_unstake(&user, user_tokens, position_id, second).await.unwrap();
+    assert_eq!(pool.token_balance(user_tokens).await, GOLD_MIN);
+    let user_stake: UserStake = pool.account(user_stake_address).await;
+    assert_eq!(user_stake.open_tickets, 0);
+    pool.close_stake(&user, user_tokens, position_id).await.unwrap();
+
+    // Closing pays out what accrued before the tickets were requested
+    assert_eq!(pool.token_balance(user_tokens).await, GOLD_MIN + user_stake.rewards_earned);
+    assert!(pool.context.banks_client.get_account(user_stake_address).await.unwrap().is_none());
+    let program_state: ProgramState = pool.account(pool.program_state).await;
+    assert_eq!(program_state.total_unbonding, 0);
+    assert_eq!(program_state.total_users, 0);
+    pool.assert_vaults_reconciled().await;
+}